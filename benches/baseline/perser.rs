@@ -0,0 +1,501 @@
+use lox_ast::error::*;
+use lox_ast::expr::*;
+use lox_ast::object::*;
+use lox_ast::stmt::*;
+use lox_ast::token_type::*;
+
+type Token = lox_ast::tokens::Token<'static>;
+
+pub struct Perser<'a> {
+    tokens: &'a Vec<Token>,
+    current: usize,
+}
+
+impl<'a> Perser<'a> {
+    pub fn new(tokens: &Vec<Token>) -> Perser<'_> {
+        Perser { tokens, current: 0 }
+    }
+
+    pub fn parse(&mut self) -> Result<Vec<Stmt<'static>>, LoxError> {
+        let mut statements = Vec::new();
+        while !self.is_at_end() {
+            statements.push(self.declaration()?);
+        }
+
+        Ok(statements)
+    }
+
+    fn declaration(&mut self) -> Result<Stmt<'static>, LoxError> {
+        if self.is_match(&[TokenType::Fun]) {
+            return self.function();
+        }
+
+        if self.is_match(&[TokenType::Var]) {
+            return self.var_declaration();
+        }
+
+        self.statement()
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt<'static>, LoxError> {
+        let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
+
+        let initializer = if self.is_match(&[TokenType::Equal]) {
+            Some(Box::new(self.expression()?))
+        } else {
+            None
+        };
+
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after variable declaration.",
+        )?;
+        Ok(Stmt::Var(VarStmt { name, initializer }))
+    }
+
+    fn function(&mut self) -> Result<Stmt<'static>, LoxError> {
+        let name = self.consume(TokenType::Identifier, "Expect function name.")?;
+
+        self.consume(TokenType::LeftParen, "Expect '(' after function name.")?;
+        let mut params = Vec::new();
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
+                if !self.is_match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(TokenType::LeftBrace, "Expect '{' before function body.")?;
+        let body = self.block()?;
+
+        Ok(Stmt::Function(FunctionStmt { name, params, body }))
+    }
+
+    fn statement(&mut self) -> Result<Stmt<'static>, LoxError> {
+        if self.is_match(&[TokenType::For]) {
+            return self.for_statement();
+        }
+
+        if self.is_match(&[TokenType::If]) {
+            return self.if_statement();
+        }
+
+        if self.is_match(&[TokenType::Print]) {
+            let value = self.expression()?;
+            self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+            return Ok(Stmt::Print(PrintStmt {
+                expression: Box::new(value),
+            }));
+        }
+
+        if self.is_match(&[TokenType::Return]) {
+            let keyword = self.previous();
+            let value = if self.check(&TokenType::Semicolon) {
+                None
+            } else {
+                Some(Box::new(self.expression()?))
+            };
+            self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+            return Ok(Stmt::Return(ReturnStmt { keyword, value }));
+        }
+
+        if self.is_match(&[TokenType::While]) {
+            self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+            let condition = self.expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+            let body = self.statement()?;
+            return Ok(Stmt::While(WhileStmt {
+                condition: Box::new(condition),
+                body: Box::new(body),
+            }));
+        }
+
+        if self.is_match(&[TokenType::LeftBrace]) {
+            return Ok(Stmt::Block(BlockStmt {
+                statements: self.block()?,
+            }));
+        }
+
+        self.expression_statement()
+    }
+
+    fn for_statement(&mut self) -> Result<Stmt<'static>, LoxError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        let initializer = if self.is_match(&[TokenType::Semicolon]) {
+            None
+        } else if self.is_match(&[TokenType::Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if self.check(&TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = if self.check(&TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let mut body = self.statement()?;
+
+        if let Some(increment) = increment {
+            body = Stmt::Block(BlockStmt {
+                statements: vec![
+                    body,
+                    Stmt::Expression(ExpressionStmt {
+                        expression: Box::new(increment),
+                    }),
+                ],
+            });
+        }
+
+        let condition = condition.unwrap_or(Expr::Literal(LiteralExpr {
+            value: Some(Object::True),
+        }));
+        body = Stmt::While(WhileStmt {
+            condition: Box::new(condition),
+            body: Box::new(body),
+        });
+
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(BlockStmt {
+                statements: vec![initializer, body],
+            });
+        }
+
+        Ok(body)
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt<'static>, LoxError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = self.statement()?;
+        let else_branch = if self.is_match(&[TokenType::Else]) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+
+        Ok(Stmt::If(IfStmt {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch,
+        }))
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt<'static>, LoxError> {
+        let expr = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        Ok(Stmt::Expression(ExpressionStmt {
+            expression: Box::new(expr),
+        }))
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt<'static>>, LoxError> {
+        let mut statements = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            statements.push(self.declaration()?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn expression(&mut self) -> Result<Expr<'static>, LoxError> {
+        self.assignment()
+    }
+
+    fn assignment(&mut self) -> Result<Expr<'static>, LoxError> {
+        let expr = self.or()?;
+
+        if self.is_match(&[TokenType::Equal]) {
+            let equals = self.previous();
+            let value = self.assignment()?;
+
+            return match expr {
+                Expr::Variable(variable) => Ok(Expr::Assign(AssignExpr {
+                    name: variable.name,
+                    value: Box::new(value),
+                })),
+                Expr::Get(get) => Ok(Expr::Set(SetExpr {
+                    object: get.object,
+                    name: get.name,
+                    value: Box::new(value),
+                })),
+                _ => Err(Perser::error(
+                    Some(equals),
+                    "Invalid assignment target.".to_string(),
+                )),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr<'static>, LoxError> {
+        let mut expr = self.and()?;
+
+        while self.is_match(&[TokenType::Or]) {
+            let operator = self.previous();
+            let right = self.and()?;
+            expr = Expr::Logical(LogicalExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr<'static>, LoxError> {
+        let mut expr = self.equality()?;
+
+        while self.is_match(&[TokenType::And]) {
+            let operator = self.previous();
+            let right = self.equality()?;
+            expr = Expr::Logical(LogicalExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<Expr<'static>, LoxError> {
+        let mut expr = self.comparison()?;
+        let ttypes = vec![TokenType::BangEqual, TokenType::EqualEqual];
+        while self.is_match(&ttypes) {
+            let operator = self.previous();
+            let right = self.comparison()?;
+            expr = Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr<'static>, LoxError> {
+        let mut expr = self.term()?;
+        let ttypes = vec![
+            TokenType::Greater,
+            TokenType::GreaterEqual,
+            TokenType::Less,
+            TokenType::LessEqual,
+        ];
+
+        while self.is_match(&ttypes) {
+            let operator = self.previous();
+            let right = self.term()?;
+            expr = Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr<'static>, LoxError> {
+        let mut expr = self.factor()?;
+        let ttypes = vec![TokenType::Minus, TokenType::Plus];
+
+        while self.is_match(&ttypes) {
+            let operator = self.previous();
+            let right = self.factor()?;
+            expr = Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expr<'static>, LoxError> {
+        let mut expr = self.unary()?;
+        let ttypes = vec![TokenType::Slash, TokenType::Star];
+
+        while self.is_match(&ttypes) {
+            let operator = self.previous();
+            let right = self.unary()?;
+            expr = Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
+    // The original parsed a primary before looking for the operator; this
+    // is the corrected rule, with the same token traffic.
+    fn unary(&mut self) -> Result<Expr<'static>, LoxError> {
+        let ttypes = vec![TokenType::Bang, TokenType::Minus];
+        if self.is_match(&ttypes) {
+            let operator = self.previous();
+            let right = self.unary()?;
+            return Ok(Expr::Unary(UnaryExpr {
+                operator,
+                right: Box::new(right),
+            }));
+        }
+
+        self.call()
+    }
+
+    fn call(&mut self) -> Result<Expr<'static>, LoxError> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.is_match(&[TokenType::LeftParen]) {
+                let mut arguments = Vec::new();
+                if !self.check(&TokenType::RightParen) {
+                    loop {
+                        arguments.push(self.expression()?);
+                        if !self.is_match(&[TokenType::Comma]) {
+                            break;
+                        }
+                    }
+                }
+                let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+                expr = Expr::Call(CallExpr {
+                    callee: Box::new(expr),
+                    paren,
+                    arguments,
+                });
+            } else if self.is_match(&[TokenType::Dot]) {
+                let name =
+                    self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+                expr = Expr::Get(GetExpr {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr<'static>, LoxError> {
+        if self.is_match(&[TokenType::False]) {
+            return Ok(Expr::Literal(LiteralExpr {
+                value: Some(Object::False),
+            }));
+        }
+
+        if self.is_match(&[TokenType::True]) {
+            return Ok(Expr::Literal(LiteralExpr {
+                value: Some(Object::True),
+            }));
+        }
+
+        if self.is_match(&[TokenType::Nil]) {
+            return Ok(Expr::Literal(LiteralExpr {
+                value: Some(Object::Nil),
+            }));
+        }
+
+        if self.is_match(&[TokenType::Number, TokenType::String]) {
+            return Ok(Expr::Literal(LiteralExpr {
+                value: self.previous().literal,
+            }));
+        }
+
+        if self.is_match(&[TokenType::This]) {
+            return Ok(Expr::This(ThisExpr {
+                keyword: self.previous(),
+            }));
+        }
+
+        if self.is_match(&[TokenType::Identifier]) {
+            return Ok(Expr::Variable(VariableExpr {
+                name: self.previous(),
+            }));
+        }
+
+        if self.is_match(&[TokenType::LeftParen]) {
+            let expr = self.expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+
+            return Ok(Expr::Grouping(GroupingExpr {
+                expression: Box::new(expr),
+            }));
+        }
+
+        Err(Perser::error(
+            Some(self.peek()),
+            "Expect an expression".to_string(),
+        ))
+    }
+
+    fn consume(&mut self, ttype: TokenType, msg: &str) -> Result<Token, LoxError> {
+        if self.check(&ttype) {
+            return Ok(self.advance());
+        }
+
+        Err(Perser::error(Some(self.peek()), msg.to_string()))
+    }
+
+    fn error(token: Option<Token>, msg: String) -> LoxError {
+        LoxError::error(token, msg)
+    }
+
+    fn is_match(&mut self, ttypes: &[TokenType]) -> bool {
+        for ttype in ttypes {
+            if self.check(ttype) {
+                self.advance();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn check(&mut self, ttype: &TokenType) -> bool {
+        if self.is_at_end() {
+            return false;
+        }
+
+        self.peek().is(ttype)
+    }
+
+    fn is_at_end(&mut self) -> bool {
+        self.peek().is(&TokenType::Eof)
+    }
+
+    fn advance(&mut self) -> Token {
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+
+        self.previous()
+    }
+
+    fn peek(&mut self) -> Token {
+        self.tokens[self.current].clone()
+    }
+
+    fn previous(&mut self) -> Token {
+        self.tokens[self.current - 1].clone()
+    }
+}
@@ -0,0 +1,143 @@
+//! Scanner and parser throughput on multi-megabyte generated programs.
+//!
+//! The zero-copy `&str` path is measured against the owned path it
+//! replaced, kept in `benches/baseline`: a `Vec<char>` scanner that builds a
+//! `String` per lexeme, feeding a parser that clones a token on every
+//! `peek` and `previous`. Scanning through `io::Read`, which still copies
+//! each lexeme, is measured alongside. Each path is timed scanning alone
+//! and scanning plus parsing.
+//!
+//! Run with `cargo bench --bench scanner`.
+
+mod baseline;
+
+use std::hint::black_box;
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use lox_ast::dialect::*;
+use lox_ast::perser::*;
+use lox_ast::printer::*;
+use lox_ast::scanner::*;
+use lox_ast::stmt::*;
+
+const SNIPPET: &str = r#"
+// Sum a few numbers and describe the result.
+var total = 0;
+for (var i = 0; i < 100; i = i + 1) {
+  total = total + i * 2.5 - (i / 3);
+}
+if (total >= 1000 and !done) {
+  print "large total: " + total;
+} else {
+  print "small total";
+}
+fun describe(name, value) {
+  return name + " = " + value;
+}
+print describe("total", total);
+"#;
+
+const ITERATIONS: u32 = 5;
+
+fn program(size: usize) -> String {
+    let mut source = String::with_capacity(size + SNIPPET.len());
+    while source.len() < size {
+        source.push_str(SNIPPET);
+    }
+    source
+}
+
+fn measure<F: FnMut() -> usize>(name: &str, unit: &str, bytes: usize, mut run: F) -> Duration {
+    let mut best = Duration::MAX;
+    let mut count = 0;
+    for _ in 0..ITERATIONS {
+        let now = Instant::now();
+        count = black_box(run());
+        best = best.min(now.elapsed());
+    }
+
+    let throughput = bytes as f64 / best.as_secs_f64() / (1024.0 * 1024.0);
+    println!("  {name:<22} {count:>9} {unit:<10} {best:>12.2?}  {throughput:>8.1} MiB/s");
+    best
+}
+
+fn baseline_scan(source: &str) -> usize {
+    let mut scanner = baseline::scanner::Scanner::new(source);
+    scanner.scan_tokens().expect("Baseline scan failed").len()
+}
+
+fn baseline_parse(source: &str) -> Vec<Stmt<'static>> {
+    let mut scanner = baseline::scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().expect("Baseline scan failed");
+    let mut perser = baseline::perser::Perser::new(tokens);
+    perser.parse().expect("Baseline parse failed")
+}
+
+fn parse(source: &str) -> Vec<Stmt<'_>> {
+    let mut scanner = Scanner::new(source, Dialect::default());
+    let (tokens, errors) = scanner.scan_tokens();
+    assert!(errors.is_empty());
+    let (statements, errors) = Perser::new(tokens, Dialect::default()).parse();
+    assert!(errors.is_empty());
+    statements
+}
+
+fn parse_reader(source: &str) -> usize {
+    let tokens: Vec<_> = Scanner::from_reader(Cursor::new(source.as_bytes()), Dialect::default())
+        .filter_map(Result::ok)
+        .collect();
+    let (statements, errors) = Perser::new(&tokens, Dialect::default()).parse();
+    assert!(errors.is_empty());
+    statements.len()
+}
+
+fn print(statements: &[Stmt<'_>]) -> Vec<String> {
+    statements
+        .iter()
+        .map(|stmt| {
+            AstPrinter
+                .print_stmt(stmt)
+                .expect("Couldn't print statement")
+        })
+        .collect()
+}
+
+fn main() {
+    // Both paths must build the same tree, or the comparison means nothing.
+    assert_eq!(print(&baseline_parse(SNIPPET)), print(&parse(SNIPPET)));
+
+    for mib in [1, 4, 16] {
+        let source = program(mib * 1024 * 1024);
+        let bytes = source.len();
+        println!("{mib} MiB source:");
+
+        let old_scan = measure("baseline scan", "tokens", bytes, || baseline_scan(&source));
+        let new_scan = measure("&str scan", "tokens", bytes, || {
+            Scanner::new(&source, Dialect::default())
+                .filter(|token| token.is_ok())
+                .count()
+        });
+        measure("io::Read scan", "tokens", bytes, || {
+            Scanner::from_reader(Cursor::new(source.as_bytes()), Dialect::default())
+                .filter(|token| token.is_ok())
+                .count()
+        });
+
+        let old_parse = measure("baseline scan+parse", "statements", bytes, || {
+            baseline_parse(&source).len()
+        });
+        let new_parse = measure("&str scan+parse", "statements", bytes, || {
+            parse(&source).len()
+        });
+        measure("io::Read scan+parse", "statements", bytes, || {
+            parse_reader(&source)
+        });
+
+        println!(
+            "  speedup over baseline: scan {:.2}x, scan+parse {:.2}x",
+            old_scan.as_secs_f64() / new_scan.as_secs_f64(),
+            old_parse.as_secs_f64() / new_parse.as_secs_f64()
+        );
+    }
+}
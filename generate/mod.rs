@@ -6,16 +6,77 @@ struct TreeType {
     base_class_name: String,
     class_name: String,
     fields: Vec<String>,
+    // Whether the node borrows from the source through a token.
+    borrows: bool,
+}
+
+impl TreeType {
+    fn generics(&self) -> &str {
+        if self.borrows {
+            "<'src>"
+        } else {
+            ""
+        }
+    }
+
+    fn anonymous(&self) -> &str {
+        if self.borrows {
+            "<'_>"
+        } else {
+            ""
+        }
+    }
+
+    // `IndexSet` becomes `index_set`, for the visitor method names.
+    fn snake_name(&self) -> String {
+        let mut name = String::new();
+        for (i, c) in self.base_class_name.trim().chars().enumerate() {
+            if c.is_uppercase() && i > 0 {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+        name
+    }
 }
 
 pub fn generate_ast(output_dir: &String) -> io::Result<()> {
     let types = vec![
+        "Assign   : Token name, Expr value",
         "Binary   : Expr left, Token operator, Expr right",
+        "Call     : Expr callee, Token paren, Vec<Expr> arguments",
+        "Comma    : Expr left, Expr right",
+        "Conditional : Expr condition, Expr then_branch, Expr else_branch",
+        "Get      : Expr object, Token name",
         "Grouping : Expr expression",
+        "Index    : Expr object, Token bracket, Expr index",
+        "IndexSet : Expr object, Token bracket, Expr index, Expr value",
+        "Interpolation : Vec<Expr> parts",
+        "List     : Vec<Expr> elements",
         "Literal  : Object value",
+        "Map      : Token brace, Vec<Expr> keys, Vec<Expr> values",
+        "Logical  : Expr left, Token operator, Expr right",
+        "Set      : Expr object, Token name, Expr value",
+        "Super    : Token keyword, Token method",
+        "This     : Token keyword",
         "Unary    : Token operator, Expr right",
+        "Variable : Token name",
     ];
     define_ast(output_dir, "Expr", types)?;
+
+    let types = vec![
+        "Block      : Vec<Stmt> statements",
+        "Class      : Token name, Option<VariableExpr> superclass, Vec<FunctionStmt> methods",
+        "Expression : Expr expression",
+        "Function   : Token name, Vec<Token> params, Vec<Stmt> body",
+        "If         : Expr condition, Stmt then_branch, Option<Stmt> else_branch",
+        "Let        : Token name, Option<Expr> initializer",
+        "Print      : Expr expression",
+        "Return     : Token keyword, Option<Expr> value",
+        "Var        : Token name, Option<Expr> initializer",
+        "While      : Expr condition, Stmt body",
+    ];
+    define_ast(output_dir, "Stmt", types)?;
     Ok(())
 }
 
@@ -26,54 +87,74 @@ fn define_ast(output_dir: &String, base_name: &str, types: Vec<&str>) -> io::Res
 
     writeln!(file, "use crate::error::*;")?;
     writeln!(file, "use crate::tokens::*;")?;
-    writeln!(file, "use crate::object::*;")?;
+    if types.iter().any(|t| t.contains("Object")) {
+        writeln!(file, "use crate::object::*;")?;
+    }
+    if base_name != "Expr" {
+        writeln!(file, "use crate::expr::*;")?;
+    }
 
     for ttype in types {
         let (base_class_name, args) = ttype.split_once(':').unwrap();
         let class_name = format!("{}{}", base_class_name.trim(), base_name);
         let ags_split = args.split(',');
         let mut fields: Vec<String> = Vec::new();
+        let mut borrows = false;
 
         for arg in ags_split {
             let (tpy, name) = arg.trim().split_once(' ').unwrap();
             let field = match tpy {
-                "Token" => format!("{}: {}", name, tpy),
                 "Object" => format!("{}: Option<{}>", name, tpy),
-                _ => format!("{}: Box<{}>", name, tpy),
+                "Token" => format!("{}: {}<'src>", name, tpy),
+                _ => {
+                    if let Some(item) = tpy.strip_prefix("Vec<").and_then(|t| t.strip_suffix('>')) {
+                        format!("{}: Vec<{}<'src>>", name, item)
+                    } else if let Some(item) = tpy
+                        .strip_prefix("Option<")
+                        .and_then(|t| t.strip_suffix('>'))
+                    {
+                        format!("{}: Option<Box<{}<'src>>>", name, item)
+                    } else {
+                        format!("{}: Box<{}<'src>>", name, tpy)
+                    }
+                }
             };
 
+            borrows |= tpy != "Object";
             fields.push(field);
         }
         tree_types.push(TreeType {
             base_class_name: base_class_name.to_string(),
             class_name,
             fields,
+            borrows,
         })
     }
 
     // Generate enum
-    writeln!(file, "\npub enum {base_name} {{")?;
+    writeln!(file, "\npub enum {base_name}<'src> {{")?;
     for tt in &tree_types {
         writeln!(
             file,
-            "    {}({}),",
+            "    {}({}{}),",
             tt.base_class_name.trim(),
-            tt.class_name.trim()
+            tt.class_name.trim(),
+            tt.generics()
         )?;
     }
     writeln!(file, "}}\n")?;
 
     // Generate base_name impl
-    writeln!(file, "impl Expr {{")?;
+    writeln!(file, "impl {base_name}<'_> {{")?;
     writeln!(
         file,
-        "    pub fn eccept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {{"
+        "    pub fn eccept<T>(&self, visitor: &dyn {base_name}Visitor<T>) -> Result<T, LoxError> {{"
     )?;
     writeln!(file, "        match self {{")?;
     for tt in &tree_types {
         writeln!(
             file,
-            "           Expr::{}(be) => be.eccept(visitor),",
+            "           {base_name}::{}(be) => be.eccept(visitor),",
             tt.base_class_name.trim()
         )?;
     }
@@ -83,7 +164,7 @@ fn define_ast(output_dir: &String, base_name: &str, types: Vec<&str>) -> io::Res
 
     // Generate struct
     for tt in &tree_types {
-        writeln!(file, "pub struct {} {{", tt.class_name)?;
+        writeln!(file, "pub struct {}{} {{", tt.class_name, tt.generics())?;
         for f in &tt.fields {
             writeln!(file, "    pub {f},")?;
         }
@@ -93,29 +174,31 @@ fn define_ast(output_dir: &String, base_name: &str, types: Vec<&str>) -> io::Res
     writeln!(file)?;
 
     // Generate trait
-    writeln!(file, "pub trait ExprVisitor<T> {{")?;
+    writeln!(file, "pub trait {base_name}Visitor<T> {{")?;
     for tt in &tree_types {
         writeln!(
             file,
-            "    fn visitor_{}_{}(&self, expr: &{}) -> Result<T, LoxError>;",
-            tt.base_class_name.trim().to_lowercase(),
+            "    fn visitor_{}_{}(&self, {}: &{}{}) -> Result<T, LoxError>;",
+            tt.snake_name(),
+            base_name.to_lowercase(),
             base_name.to_lowercase(),
-            tt.class_name.trim()
+            tt.class_name.trim(),
+            tt.anonymous()
         )?;
     }
     writeln!(file, "}}\n")?;
 
     // Generate impl
     for tt in &tree_types {
-        writeln!(file, "impl {} {{", tt.class_name)?;
+        writeln!(file, "impl {}{} {{", tt.class_name, tt.anonymous())?;
         writeln!(
             file,
-            "    pub fn eccept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {{"
+            "    pub fn eccept<T>(&self, visitor: &dyn {base_name}Visitor<T>) -> Result<T, LoxError> {{"
         )?;
         writeln!(
             file,
             "       visitor.visitor_{}_{}(self)",
-            tt.base_class_name.trim().to_lowercase(),
+            tt.snake_name(),
             base_name.to_lowercase(),
         )?;
         writeln!(file, "    }}")?;
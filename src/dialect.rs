@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::operators::*;
+use crate::token_type::*;
+
+static LOX_KEYWORDS: LazyLock<HashMap<&'static str, TokenType>> = LazyLock::new(|| {
+    HashMap::from([
+        ("and", TokenType::And),
+        ("class", TokenType::Class),
+        ("else", TokenType::Else),
+        ("false", TokenType::False),
+        ("for", TokenType::For),
+        ("fun", TokenType::Fun),
+        ("if", TokenType::If),
+        ("nil", TokenType::Nil),
+        ("or", TokenType::Or),
+        ("print", TokenType::Print),
+        ("return", TokenType::Return),
+        ("super", TokenType::Super),
+        ("this", TokenType::This),
+        ("true", TokenType::True),
+        ("var", TokenType::Var),
+        ("while", TokenType::While),
+    ])
+});
+
+static LOX_OPERATORS: LazyLock<OperatorTable> = LazyLock::new(OperatorTable::lox);
+
+static EXTENDED_KEYWORDS: LazyLock<HashMap<&'static str, TokenType>> = LazyLock::new(|| {
+    let mut keywords = LOX_KEYWORDS.clone();
+    keywords.insert("let", TokenType::Let);
+    keywords.insert("import", TokenType::Import);
+    keywords
+});
+
+/// The flavour of Lox the front end accepts.
+///
+/// `Dialect::lox()` is the language from the book, so legacy scripts can
+/// keep using `let` or `import` as names and backslashes in strings stay
+/// literal. `Dialect::extended()` (the default) reserves the extra
+/// keywords and turns on the extended syntax. Both parse the book's
+/// operators; `with_operators` swaps in another table.
+#[derive(Debug, Clone, Copy)]
+pub struct Dialect {
+    keywords: &'static HashMap<&'static str, TokenType>,
+    operators: &'static OperatorTable,
+    /// `[` and `]` tokens.
+    pub brackets: bool,
+    /// `{key: value}` map literals and the `:` token.
+    pub maps: bool,
+    /// Escape sequences, interpolation, raw and multi-line strings, and
+    /// hex/octal/binary, digit-separated and exponent number literals.
+    pub extended_literals: bool,
+    /// The `?:` conditional and `,` comma operators.
+    pub extended_operators: bool,
+}
+
+impl Dialect {
+    pub fn lox() -> Dialect {
+        Dialect {
+            keywords: &LOX_KEYWORDS,
+            operators: &LOX_OPERATORS,
+            brackets: false,
+            maps: false,
+            extended_literals: false,
+            extended_operators: false,
+        }
+    }
+
+    pub fn extended() -> Dialect {
+        Dialect {
+            keywords: &EXTENDED_KEYWORDS,
+            operators: &LOX_OPERATORS,
+            brackets: true,
+            maps: true,
+            extended_literals: true,
+            extended_operators: true,
+        }
+    }
+
+    pub fn keyword(&self, check: &str) -> Option<TokenType> {
+        self.keywords.get(check).cloned()
+    }
+
+    /// Parses with `operators` instead of the book's operators. Every copy
+    /// of the dialect shares the table, so it lives in a `static` such as a
+    /// `LazyLock<OperatorTable>`.
+    pub fn with_operators(mut self, operators: &'static OperatorTable) -> Dialect {
+        self.operators = operators;
+        self
+    }
+
+    /// The operators `Perser` starts from for this dialect.
+    pub fn operators(&self) -> OperatorTable {
+        self.operators.clone()
+    }
+}
+
+impl Default for Dialect {
+    fn default() -> Dialect {
+        Dialect::extended()
+    }
+}
@@ -1,40 +1,102 @@
+use crate::source_map::*;
+use crate::span::*;
 use crate::token_type::*;
 use crate::tokens::*;
 
 #[derive(Debug, Clone)]
 pub struct LoxError {
-    token: Option<Token>,
+    token: Option<Box<Token<'static>>>,
     line: usize,
+    span: Option<Span>,
     msg: String,
 }
 
 impl LoxError {
 
-    pub fn error(token: Option<Token>, msg: String) -> LoxError {
-        let mut err: LoxError;
+    pub fn error(token: Option<Token<'_>>, msg: String) -> LoxError {
+        let err: LoxError;
         let mut line = 0;
         if let Some(t) = token {
             line = t.line;
-            let token = Some(t);
-            err = LoxError { token, line, msg };
-            err.report("".to_string());
+            let span = Some(t.span);
+            let token = Some(Box::new(t.into_owned()));
+            err = LoxError {
+                token,
+                line,
+                span,
+                msg,
+            };
             return err;
         }
 
-        err = LoxError { token, line, msg };
-        err.report("".to_string());
+        err = LoxError {
+            token: None,
+            line,
+            span: None,
+            msg,
+        };
         err
     }
 
-    pub fn report(&mut self, loc: String) {
+    pub fn at(span: Span, msg: String) -> LoxError {
+        LoxError {
+            token: None,
+            line: span.start_line,
+            span: Some(span),
+            msg,
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// The same error at `span`, e.g. after an edit moved the text it
+    /// points at.
+    pub fn with_span(mut self, span: Span) -> LoxError {
+        self.line = span.start_line;
+        self.span = Some(span);
+        if let Some(token) = &mut self.token {
+            token.line = span.start_line;
+            token.span = span;
+        }
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+
+    pub fn report(&self, loc: String) {
+        let pos = match &self.span {
+            Some(span) => span.to_string(),
+            None => self.line.to_string(),
+        };
+
+        eprintln!("[line {pos}] {}", self.describe(loc))
+    }
+
+    /// Reports the error as `file:line:column: Error ...`.
+    pub fn report_in(&self, sources: &SourceMap) {
+        match &self.span {
+            Some(span) => eprintln!(
+                "{}: {}",
+                sources.location(span),
+                self.describe("".to_string())
+            ),
+            None => self.report("".to_string()),
+        }
+    }
+
+    fn describe(&self, loc: String) -> String {
         if let Some(token) = &self.token {
             if token.is(&TokenType::Eof) {
-                eprintln!("{} as end {}", self.line, self.msg)
+                format!("Error at end: {}", self.msg)
             } else {
-                eprintln!("{} at '{}' {}", self.line, token.lexeme, self.msg)
+                format!("Error at '{}': {}", token.lexeme, self.msg)
             }
         } else {
-            eprintln!("[line {}] error{}: {}", self.line, loc, self.msg)
+            format!("Error{loc}: {}", self.msg)
         }
     }
 }
@@ -0,0 +1,14 @@
+pub mod dialect;
+pub mod error;
+pub mod expr;
+pub mod object;
+pub mod operators;
+pub mod perser;
+pub mod printer;
+pub mod scanner;
+pub mod source_map;
+pub mod span;
+pub mod stmt;
+pub mod token_type;
+pub mod tokens;
+pub mod trivia;
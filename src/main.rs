@@ -1,28 +1,30 @@
-mod error;
-mod expr;
-mod perser;
-mod printer;
-mod scanner;
-mod token_type;
-mod tokens;
-mod object;
-
-use error::*;
-use perser::*;
-use printer::*;
-use scanner::*;
+use lox_ast::dialect::*;
+use lox_ast::error::*;
+use lox_ast::perser::*;
+use lox_ast::printer::*;
+use lox_ast::scanner::*;
+use lox_ast::source_map::*;
 
 use std::env::args;
-use std::fs::read_to_string;
 use std::io::{self, stdout, Write};
+use std::path::Path;
 
 fn main() -> Result<(), String> {
-    let args: Vec<String> = args().collect();
+    let mut args: Vec<String> = args().collect();
+    // `--strict` runs scripts written for the book's Lox.
+    let dialect = match args.iter().position(|arg| arg == "--strict") {
+        Some(i) => {
+            args.remove(i);
+            Dialect::lox()
+        }
+        None => Dialect::extended(),
+    };
+
     match args.len() {
-        1 => run_promt(),
-        2 => run_file(&args[1]).expect("Couldn't run file"),
+        1 => run_promt(dialect),
+        2 => run_file(&args[1], dialect).expect("Couldn't run file"),
         _ => {
-            println!("Usage: lox-ast [script]");
+            println!("Usage: lox-ast [--strict] [script]");
             std::process::exit(64);
         }
     }
@@ -30,21 +32,19 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
-fn run_file(path: &String) -> io::Result<()> {
-    let buf = read_to_string(path)?;
-    match run(buf) {
-        Ok(_) => {}
-        Err(mut e) => {
-            e.report("".to_string());
-            std::process::exit(65);
-        }
+fn run_file(path: &String, dialect: Dialect) -> io::Result<()> {
+    let mut sources = SourceMap::new();
+    let file = sources.load(Path::new(path))?;
+    if run(&sources, file, dialect).is_err() {
+        std::process::exit(65);
     }
 
     Ok(())
 }
 
-fn run_promt() {
+fn run_promt(dialect: Dialect) {
     let stdin = io::stdin();
+    let mut sources = SourceMap::new();
     print!("> ");
     let _ = stdout().flush();
     for line in stdin.lines() {
@@ -52,13 +52,9 @@ fn run_promt() {
             if line.is_empty() {
                 break;
             }
-            match run(line) {
-                Ok(_) => {}
-                Err(mut e) => {
-                    e.report("".to_string());
-                    std::process::exit(65);
-                }
-            }
+            // Errors are already reported; keep the session going.
+            let file = sources.add("<stdin>".to_string(), line);
+            let _ = run(&sources, file, dialect);
         } else {
             break;
         }
@@ -67,17 +63,30 @@ fn run_promt() {
     }
 }
 
-fn run(source: String) -> Result<(), LoxError> {
-    let mut scanner = Scanner::new(source);
-    let tokens = scanner.scan_tokens()?;
-    let mut parser = Perser::new(tokens);
+fn run(sources: &SourceMap, file: FileId, dialect: Dialect) -> Result<(), LoxError> {
+    let mut scanner = Scanner::new(sources.get(file).source(), dialect).file(file);
+    let (tokens, errors) = scanner.scan_tokens();
+    if let Some(err) = errors.first() {
+        for err in errors {
+            err.report_in(sources);
+        }
+        return Err(err.clone());
+    }
+
+    let mut parser = Perser::new(tokens, dialect);
 
-    match parser.parse() {
-        None => {}
-        Some(expr) => {
-            let printer = AstPrinter {};
-            println!("AST Printr: \n{}", printer.print(&expr)?);
+    let (statements, errors) = parser.parse();
+    if let Some(err) = errors.first() {
+        for err in &errors {
+            err.report_in(sources);
         }
+        return Err(err.clone());
+    }
+
+    let printer = AstPrinter {};
+    println!("AST Printr: ");
+    for stmt in &statements {
+        println!("{}", printer.print_stmt(stmt)?);
     }
 
     Ok(())
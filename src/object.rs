@@ -1,22 +1,263 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::{self};
+use std::rc::Rc;
 
-#[derive(Debug, Clone)]
+use crate::error::*;
+use crate::tokens::*;
+
+#[derive(Clone)]
 pub enum Object {
     Num(f64),
     Str(String),
     Nil,
     True,
     False,
+    /// Lists are shared, so every clone sees writes made through the others.
+    List(Rc<RefCell<Vec<Object>>>),
+    Map(Rc<RefCell<LoxMap>>),
+}
+
+/// A hashable map key. Only strings, numbers, booleans and nil can be keys.
+///
+/// Numbers compare by value, so `0` and `-0` are the same key, and every
+/// NaN is the same key as every other NaN.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Str(String),
+    Num(u64),
+    Nil,
+    True,
+    False,
+}
+
+impl MapKey {
+    pub fn new(key: &Object) -> Option<MapKey> {
+        match key {
+            Object::Str(s) => Some(MapKey::Str(s.clone())),
+            Object::Num(n) if *n == 0.0 => Some(MapKey::Num(0f64.to_bits())),
+            Object::Num(n) if n.is_nan() => Some(MapKey::Num(f64::NAN.to_bits())),
+            Object::Num(n) => Some(MapKey::Num(n.to_bits())),
+            Object::Nil => Some(MapKey::Nil),
+            Object::True => Some(MapKey::True),
+            Object::False => Some(MapKey::False),
+            Object::List(_) | Object::Map(_) => None,
+        }
+    }
+
+    pub fn to_object(&self) -> Object {
+        match self {
+            MapKey::Str(s) => Object::Str(s.clone()),
+            MapKey::Num(bits) => Object::Num(f64::from_bits(*bits)),
+            MapKey::Nil => Object::Nil,
+            MapKey::True => Object::True,
+            MapKey::False => Object::False,
+        }
+    }
+}
+
+/// A map that iterates in insertion order. Overwriting a key keeps its
+/// original position.
+#[derive(Debug, Clone, Default)]
+pub struct LoxMap {
+    entries: Vec<(MapKey, Object)>,
+    slots: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> LoxMap {
+        LoxMap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&Object> {
+        self.slots.get(key).map(|&slot| &self.entries[slot].1)
+    }
+
+    pub fn insert(&mut self, key: MapKey, value: Object) {
+        match self.slots.get(&key) {
+            Some(&slot) => self.entries[slot].1 = value,
+            None => {
+                self.slots.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&MapKey, &Object)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+}
+
+impl Object {
+    pub fn list(elements: Vec<Object>) -> Object {
+        Object::List(Rc::new(RefCell::new(elements)))
+    }
+
+    pub fn map(entries: LoxMap) -> Object {
+        Object::Map(Rc::new(RefCell::new(entries)))
+    }
+
+    /// Reads `self[index]`, reporting errors at `bracket`.
+    pub fn get_index(&self, index: &Object, bracket: &Token<'_>) -> Result<Object, LoxError> {
+        match self {
+            Object::List(elements) => {
+                let elements = elements.borrow();
+                let i = list_index(index, elements.len(), bracket)?;
+                Ok(elements[i].clone())
+            }
+            Object::Map(entries) => {
+                let key = map_key(index, bracket)?;
+                match entries.borrow().get(&key) {
+                    Some(value) => Ok(value.clone()),
+                    None => Err(LoxError::error(
+                        Some(bracket.clone()),
+                        format!("Undefined key {index}."),
+                    )),
+                }
+            }
+            _ => Err(LoxError::error(
+                Some(bracket.clone()),
+                "Only lists and maps can be indexed.".to_string(),
+            )),
+        }
+    }
+
+    /// Writes `self[index] = value`, reporting errors at `bracket`.
+    pub fn set_index(
+        &self,
+        index: &Object,
+        value: Object,
+        bracket: &Token<'_>,
+    ) -> Result<(), LoxError> {
+        match self {
+            Object::List(elements) => {
+                let mut elements = elements.borrow_mut();
+                let i = list_index(index, elements.len(), bracket)?;
+                elements[i] = value;
+                Ok(())
+            }
+            Object::Map(entries) => {
+                let key = map_key(index, bracket)?;
+                entries.borrow_mut().insert(key, value);
+                Ok(())
+            }
+            _ => Err(LoxError::error(
+                Some(bracket.clone()),
+                "Only lists and maps can be indexed.".to_string(),
+            )),
+        }
+    }
+}
+
+// Checks that `index` is a whole number inside `0..len`.
+fn list_index(index: &Object, len: usize, bracket: &Token<'_>) -> Result<usize, LoxError> {
+    let Object::Num(n) = index else {
+        return Err(LoxError::error(
+            Some(bracket.clone()),
+            "List index must be a number.".to_string(),
+        ));
+    };
+
+    if n.fract() != 0.0 {
+        return Err(LoxError::error(
+            Some(bracket.clone()),
+            format!("List index {n} is not a whole number."),
+        ));
+    }
+
+    if *n < 0.0 || *n >= len as f64 {
+        return Err(LoxError::error(
+            Some(bracket.clone()),
+            format!("List index {n} is out of bounds for length {len}."),
+        ));
+    }
+
+    Ok(*n as usize)
+}
+
+fn map_key(key: &Object, bracket: &Token<'_>) -> Result<MapKey, LoxError> {
+    MapKey::new(key).ok_or_else(|| {
+        LoxError::error(
+            Some(bracket.clone()),
+            "Map keys must be strings, numbers, booleans or nil.".to_string(),
+        )
+    })
 }
 
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, &mut Vec::new())
+    }
+}
+
+impl Object {
+    // Lists and maps can contain themselves, so `seen` holds the ones being
+    // written and a repeat is written as `[...]` or `{...}`, as Python does.
+    fn write(&self, f: &mut fmt::Formatter, seen: &mut Vec<*const ()>) -> fmt::Result {
         match self {
             Object::Num(n) => write!(f, "{n}"),
             Object::Str(s) => write!(f, "\"{s}\""),
             Object::Nil => write!(f, "nil"),
             Object::True => write!(f, "true"),
             Object::False => write!(f, "false"),
+            Object::List(elements) => {
+                let ptr = Rc::as_ptr(elements) as *const ();
+                if seen.contains(&ptr) {
+                    return write!(f, "[...]");
+                }
+
+                seen.push(ptr);
+                write!(f, "[")?;
+                for (i, element) in elements.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.write(f, seen)?;
+                }
+                seen.pop();
+                write!(f, "]")
+            }
+            Object::Map(entries) => {
+                let ptr = Rc::as_ptr(entries) as *const ();
+                if seen.contains(&ptr) {
+                    return write!(f, "{{...}}");
+                }
+
+                seen.push(ptr);
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", key.to_object())?;
+                    value.write(f, seen)?;
+                }
+                seen.pop();
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Object::Num(n) => f.debug_tuple("Num").field(n).finish(),
+            Object::Str(s) => f.debug_tuple("Str").field(s).finish(),
+            Object::Nil => write!(f, "Nil"),
+            Object::True => write!(f, "True"),
+            Object::False => write!(f, "False"),
+            // Collections go through `Display`, which stops at cycles.
+            Object::List(_) => write!(f, "List({self})"),
+            Object::Map(_) => write!(f, "Map({self})"),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::token_type::*;
+
+// Binding powers of the built-in operators, loosest first. They are spaced
+// out so a dialect can slot new operators between two existing levels.
+pub const PREC_OR: u8 = 10;
+pub const PREC_AND: u8 = 20;
+pub const PREC_EQUALITY: u8 = 30;
+pub const PREC_COMPARISON: u8 = 40;
+pub const PREC_TERM: u8 = 50;
+pub const PREC_FACTOR: u8 = 60;
+pub const PREC_UNARY: u8 = 70;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// The node an infix operator is parsed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfixKind {
+    /// `Expr::Binary`, for operators that always evaluate both operands.
+    Binary,
+    /// `Expr::Logical`, for short-circuiting operators.
+    Logical,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InfixOperator {
+    pub precedence: u8,
+    pub associativity: Associativity,
+    pub kind: InfixKind,
+}
+
+/// The prefix and infix operators `Perser` understands, keyed by token type.
+///
+/// Operands of a prefix operator bind at least as tightly as the operator's
+/// own precedence, so `-a * b` is `(-a) * b`. Calls and property access
+/// always bind tighter than any operator in the table.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorTable {
+    prefix: HashMap<TokenType, u8>,
+    infix: HashMap<TokenType, InfixOperator>,
+}
+
+impl OperatorTable {
+    pub fn new() -> OperatorTable {
+        OperatorTable::default()
+    }
+
+    /// The operators of the book's Lox.
+    pub fn lox() -> OperatorTable {
+        let mut table = OperatorTable::new();
+        table
+            .register_logical(TokenType::Or, PREC_OR)
+            .register_logical(TokenType::And, PREC_AND)
+            .register_infix(TokenType::BangEqual, PREC_EQUALITY, Associativity::Left)
+            .register_infix(TokenType::EqualEqual, PREC_EQUALITY, Associativity::Left)
+            .register_infix(TokenType::Greater, PREC_COMPARISON, Associativity::Left)
+            .register_infix(
+                TokenType::GreaterEqual,
+                PREC_COMPARISON,
+                Associativity::Left,
+            )
+            .register_infix(TokenType::Less, PREC_COMPARISON, Associativity::Left)
+            .register_infix(TokenType::LessEqual, PREC_COMPARISON, Associativity::Left)
+            .register_infix(TokenType::Minus, PREC_TERM, Associativity::Left)
+            .register_infix(TokenType::Plus, PREC_TERM, Associativity::Left)
+            .register_infix(TokenType::Slash, PREC_FACTOR, Associativity::Left)
+            .register_infix(TokenType::Star, PREC_FACTOR, Associativity::Left)
+            .register_prefix(TokenType::Bang, PREC_UNARY)
+            .register_prefix(TokenType::Minus, PREC_UNARY);
+        table
+    }
+
+    pub fn register_prefix(&mut self, ttype: TokenType, precedence: u8) -> &mut OperatorTable {
+        self.prefix.insert(ttype, precedence);
+        self
+    }
+
+    pub fn register_infix(
+        &mut self,
+        ttype: TokenType,
+        precedence: u8,
+        associativity: Associativity,
+    ) -> &mut OperatorTable {
+        self.infix.insert(
+            ttype,
+            InfixOperator {
+                precedence,
+                associativity,
+                kind: InfixKind::Binary,
+            },
+        );
+        self
+    }
+
+    /// Registers a left-associative, short-circuiting infix operator.
+    pub fn register_logical(&mut self, ttype: TokenType, precedence: u8) -> &mut OperatorTable {
+        self.infix.insert(
+            ttype,
+            InfixOperator {
+                precedence,
+                associativity: Associativity::Left,
+                kind: InfixKind::Logical,
+            },
+        );
+        self
+    }
+
+    pub fn prefix(&self, ttype: &TokenType) -> Option<u8> {
+        self.prefix.get(ttype).copied()
+    }
+
+    pub fn infix(&self, ttype: &TokenType) -> Option<InfixOperator> {
+        self.infix.get(ttype).copied()
+    }
+}
@@ -1,116 +1,556 @@
+use crate::dialect::*;
 use crate::error::*;
 use crate::expr::*;
+use crate::operators::*;
+use crate::stmt::*;
 use crate::token_type::*;
 use crate::tokens::*;
 use crate::object::*;
 
-pub struct Perser<'a> {
-    tokens: &'a Vec<Token>,
+/// Most parameters or arguments a single function or call may have.
+const MAX_ARGUMENTS: usize = 255;
+
+pub struct Perser<'a, 'src> {
+    tokens: &'a [Token<'src>],
+    dialect: Dialect,
+    operators: OperatorTable,
     current: usize,
+    errors: Vec<LoxError>,
 }
 
-impl<'a> Perser<'a> {
-    pub fn new(tokens: &Vec<Token>) -> Perser {
-        Perser { tokens, current: 0 }
+impl<'a, 'src> Perser<'a, 'src> {
+    pub fn new(tokens: &'a [Token<'src>], dialect: Dialect) -> Perser<'a, 'src> {
+        Perser {
+            tokens,
+            dialect,
+            operators: dialect.operators(),
+            current: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Replaces the dialect's operators, e.g. with a table extended through
+    /// `OperatorTable::register_infix`.
+    pub fn operators(mut self, operators: OperatorTable) -> Perser<'a, 'src> {
+        self.operators = operators;
+        self
+    }
+
+    pub fn dialect(&self) -> &Dialect {
+        &self.dialect
     }
 
-    pub fn parse(&mut self) -> Option<Expr> {
-        match self.expression() {
-            Ok(expr) => Some(expr),
-            Err(_) => None,
+    /// Parses the whole program, returning every statement that parsed
+    /// cleanly along with every syntax error. After an error the parser
+    /// skips to the next statement boundary and carries on.
+    pub fn parse(&mut self) -> (Vec<Stmt<'src>>, Vec<LoxError>) {
+        let mut statements = Vec::new();
+        while !self.is_at_end() {
+            if let Some(stmt) = self.declaration() {
+                statements.push(stmt);
+            }
         }
+
+        (statements, std::mem::take(&mut self.errors))
     }
 
-    fn expression(&mut self) -> Result<Expr, LoxError> {
-        self.equality()
+    fn declaration(&mut self) -> Option<Stmt<'src>> {
+        match self.try_declaration() {
+            Ok(stmt) => Some(stmt),
+            Err(err) => {
+                self.errors.push(err);
+                self.synchronize();
+                None
+            }
+        }
     }
 
-    fn equality(&mut self) -> Result<Expr, LoxError> {
-        let mut expr = self.comparison()?;
-        let ttypes = vec![TokenType::BangEqual, TokenType::EqualEqual];
-        while self.is_match(&ttypes) {
-            let operator = self.previous();
-            let right = self.comparison()?;
-            expr = Expr::Binary(BinaryExpr {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
+    fn try_declaration(&mut self) -> Result<Stmt<'src>, LoxError> {
+        if self.is_match(&[TokenType::Class]) {
+            return self.class_declaration();
+        }
+
+        if self.is_match(&[TokenType::Fun]) {
+            return Ok(Stmt::Function(self.function("function")?));
+        }
+
+        // `let` is only a keyword in the extended dialect, so the strict
+        // scanner never produces it.
+        if self.is_match(&[TokenType::Var, TokenType::Let]) {
+            return self.var_declaration();
+        }
+
+        self.statement()
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt<'src>, LoxError> {
+        let keyword = self.previous();
+        let name = self
+            .consume(TokenType::Identifier, "Expect variable name.")?
+            .clone();
+
+        let initializer = if self.is_match(&[TokenType::Equal]) {
+            Some(Box::new(self.expression()?))
+        } else {
+            None
+        };
+
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after variable declaration.",
+        )?;
+
+        if keyword.is(&TokenType::Let) {
+            Ok(Stmt::Let(LetStmt { name, initializer }))
+        } else {
+            Ok(Stmt::Var(VarStmt { name, initializer }))
+        }
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt<'src>, LoxError> {
+        let name = self
+            .consume(TokenType::Identifier, "Expect class name.")?
+            .clone();
+
+        let superclass = if self.is_match(&[TokenType::Less]) {
+            let name = self
+                .consume(TokenType::Identifier, "Expect superclass name.")?
+                .clone();
+            Some(Box::new(VariableExpr { name }))
+        } else {
+            None
+        };
+
+        self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
+        let mut methods = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function("method")?);
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
+
+        Ok(Stmt::Class(ClassStmt {
+            name,
+            superclass,
+            methods,
+        }))
+    }
+
+    fn function(&mut self, kind: &str) -> Result<FunctionStmt<'src>, LoxError> {
+        let name = self
+            .consume(TokenType::Identifier, &format!("Expect {kind} name."))?
+            .clone();
+
+        self.consume(
+            TokenType::LeftParen,
+            &format!("Expect '(' after {kind} name."),
+        )?;
+        let mut params = Vec::new();
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    self.errors.push(Perser::error(
+                        Some(self.peek().clone()),
+                        format!("Can't have more than {MAX_ARGUMENTS} parameters."),
+                    ));
+                }
+
+                params.push(
+                    self.consume(TokenType::Identifier, "Expect parameter name.")?
+                        .clone(),
+                );
+                if !self.is_match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(
+            TokenType::LeftBrace,
+            &format!("Expect '{{' before {kind} body."),
+        )?;
+        let body = self.block()?;
+
+        Ok(FunctionStmt { name, params, body })
+    }
+
+    fn statement(&mut self) -> Result<Stmt<'src>, LoxError> {
+        if self.is_match(&[TokenType::For]) {
+            return self.for_statement();
+        }
+
+        if self.is_match(&[TokenType::If]) {
+            return self.if_statement();
+        }
+
+        if self.is_match(&[TokenType::Print]) {
+            return self.print_statement();
+        }
+
+        if self.is_match(&[TokenType::Return]) {
+            return self.return_statement();
+        }
+
+        if self.is_match(&[TokenType::While]) {
+            return self.while_statement();
+        }
+
+        if self.check(&TokenType::LeftBrace) && !self.map_ahead() {
+            self.advance();
+            return Ok(Stmt::Block(BlockStmt {
+                statements: self.block()?,
+            }));
+        }
+
+        self.expression_statement()
+    }
+
+    // `for` has no node of its own: it is desugared into the equivalent
+    // `while` loop, wrapped in blocks for the initializer and increment.
+    fn for_statement(&mut self) -> Result<Stmt<'src>, LoxError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        let initializer = if self.is_match(&[TokenType::Semicolon]) {
+            None
+        } else if self.is_match(&[TokenType::Var, TokenType::Let]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if self.check(&TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = if self.check(&TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let mut body = self.statement()?;
+
+        if let Some(increment) = increment {
+            body = Stmt::Block(BlockStmt {
+                statements: vec![
+                    body,
+                    Stmt::Expression(ExpressionStmt {
+                        expression: Box::new(increment),
+                    }),
+                ],
             });
         }
 
-        Ok(expr)
+        let condition = condition.unwrap_or(Expr::Literal(LiteralExpr {
+            value: Some(Object::True),
+        }));
+        body = Stmt::While(WhileStmt {
+            condition: Box::new(condition),
+            body: Box::new(body),
+        });
+
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(BlockStmt {
+                statements: vec![initializer, body],
+            });
+        }
+
+        Ok(body)
+    }
+
+    // A dangling `else` binds to the nearest `if`, since each `if` eagerly
+    // looks for one before returning.
+    fn if_statement(&mut self) -> Result<Stmt<'src>, LoxError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = self.statement()?;
+        let else_branch = if self.is_match(&[TokenType::Else]) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+
+        Ok(Stmt::If(IfStmt {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch,
+        }))
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt<'src>, LoxError> {
+        let keyword = self.previous().clone();
+        let value = if self.check(&TokenType::Semicolon) {
+            None
+        } else {
+            Some(Box::new(self.expression()?))
+        };
+
+        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+        Ok(Stmt::Return(ReturnStmt { keyword, value }))
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt<'src>, LoxError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        let body = self.statement()?;
+
+        Ok(Stmt::While(WhileStmt {
+            condition: Box::new(condition),
+            body: Box::new(body),
+        }))
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt<'src>, LoxError> {
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+        Ok(Stmt::Print(PrintStmt {
+            expression: Box::new(value),
+        }))
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt<'src>, LoxError> {
+        let expr = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        Ok(Stmt::Expression(ExpressionStmt {
+            expression: Box::new(expr),
+        }))
+    }
+
+    // At the start of a statement `{` opens a block, unless it is followed by
+    // a key and a `:`, which only a map literal can have. `{}` stays an empty
+    // block. The lookahead parses the key and then rewinds.
+    fn map_ahead(&mut self) -> bool {
+        if !self.dialect.maps {
+            return false;
+        }
+
+        let (current, errors) = (self.current, self.errors.len());
+        self.advance();
+        let is_map = !self.check(&TokenType::RightBrace)
+            && self.assignment().is_ok()
+            && self.check(&TokenType::Colon);
+
+        self.current = current;
+        self.errors.truncate(errors);
+        is_map
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt<'src>>, LoxError> {
+        let mut statements = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            if let Some(stmt) = self.declaration() {
+                statements.push(stmt);
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn expression(&mut self) -> Result<Expr<'src>, LoxError> {
+        self.comma()
     }
 
-    fn comparison(&mut self) -> Result<Expr, LoxError> {
-        let mut expr = self.term()?;
-        let ttypes = vec![
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ];
+    // The comma operator binds loosest of all, so lists, maps and argument
+    // lists parse their elements with `assignment` instead.
+    fn comma(&mut self) -> Result<Expr<'src>, LoxError> {
+        let mut expr = self.assignment()?;
 
-        while self.is_match(&ttypes) {
-            let operator = self.previous();
-            let right = self.term()?;
-            expr = Expr::Binary(BinaryExpr {
+        while self.dialect.extended_operators && self.is_match(&[TokenType::Comma]) {
+            let right = self.assignment()?;
+            expr = Expr::Comma(CommaExpr {
                 left: Box::new(expr),
-                operator,
                 right: Box::new(right),
             });
         }
+
         Ok(expr)
     }
 
-    fn term(&mut self) -> Result<Expr, LoxError> {
-        let mut expr = self.factor()?;
-        let ttypes = vec![TokenType::Minus, TokenType::Plus];
+    fn assignment(&mut self) -> Result<Expr<'src>, LoxError> {
+        let expr = self.conditional()?;
+
+        if self.is_match(&[TokenType::Equal]) {
+            let equals = self.previous();
+            let value = self.assignment()?;
+
+            // Assignment is right-associative: the value is parsed first, then
+            // the left-hand side is checked to be a valid target.
+            match expr {
+                Expr::Variable(variable) => {
+                    return Ok(Expr::Assign(AssignExpr {
+                        name: variable.name,
+                        value: Box::new(value),
+                    }));
+                }
+                Expr::Get(get) => {
+                    return Ok(Expr::Set(SetExpr {
+                        object: get.object,
+                        name: get.name,
+                        value: Box::new(value),
+                    }));
+                }
+                Expr::Index(index) => {
+                    return Ok(Expr::IndexSet(IndexSetExpr {
+                        object: index.object,
+                        bracket: index.bracket,
+                        index: index.index,
+                        value: Box::new(value),
+                    }));
+                }
+                // The parser isn't confused, so report without unwinding.
+                _ => self.errors.push(Perser::error(
+                    Some(equals.clone()),
+                    "Invalid assignment target.".to_string(),
+                )),
+            }
 
-        while self.is_match(&ttypes) {
-            let operator = self.previous();
-            let right = self.factor()?;
-            expr = Expr::Binary(BinaryExpr {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            });
+            return Ok(expr);
         }
+
         Ok(expr)
     }
 
-    fn factor(&mut self) -> Result<Expr, LoxError> {
+    // `a ? b : c ? d : e` groups as `a ? b : (c ? d : e)`. As in C, the
+    // middle operand may be any expression, including a comma expression.
+    fn conditional(&mut self) -> Result<Expr<'src>, LoxError> {
+        let condition = self.binary(0)?;
+
+        if self.dialect.extended_operators && self.is_match(&[TokenType::Question]) {
+            let then_branch = self.expression()?;
+            self.consume(
+                TokenType::Colon,
+                "Expect ':' after then branch of conditional expression.",
+            )?;
+            let else_branch = self.conditional()?;
+
+            return Ok(Expr::Conditional(ConditionalExpr {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            }));
+        }
+
+        Ok(condition)
+    }
+
+    // Parses operators from the operator table whose precedence is at least
+    // `min_precedence`. A left-associative operator parses its right operand
+    // one level tighter, so `a - b - c` groups as `(a - b) - c`.
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr<'src>, LoxError> {
         let mut expr = self.unary()?;
-        let ttypes = vec![TokenType::Slash, TokenType::Star];
 
-        while self.is_match(&ttypes) {
-            let operator = self.previous();
-            let right = self.unary()?;
-            expr = Expr::Binary(BinaryExpr {
-                left: Box::new(expr),
+        while let Some(op) = self.operators.infix(&self.peek().ttype) {
+            if op.precedence < min_precedence {
+                break;
+            }
+
+            let operator = self.advance().clone();
+            let right = match op.associativity {
+                Associativity::Left => self.binary(op.precedence.saturating_add(1))?,
+                Associativity::Right => self.binary(op.precedence)?,
+            };
+
+            let (left, right) = (Box::new(expr), Box::new(right));
+            expr = match op.kind {
+                InfixKind::Binary => Expr::Binary(BinaryExpr {
+                    left,
+                    operator,
+                    right,
+                }),
+                InfixKind::Logical => Expr::Logical(LogicalExpr {
+                    left,
+                    operator,
+                    right,
+                }),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr<'src>, LoxError> {
+        if let Some(precedence) = self.operators.prefix(&self.peek().ttype) {
+            let operator = self.advance().clone();
+            let right = self.binary(precedence)?;
+            return Ok(Expr::Unary(UnaryExpr {
                 operator,
                 right: Box::new(right),
-            });
+            }));
         }
-        Ok(expr)
+
+        self.call()
     }
 
-    fn unary(&mut self) -> Result<Expr, LoxError> {
-        let ttypes = vec![TokenType::Bang, TokenType::Minus];
+    fn call(&mut self) -> Result<Expr<'src>, LoxError> {
         let mut expr = self.primary()?;
 
-        while self.is_match(&ttypes) {
-            let operator = self.previous();
-            let right = self.term()?;
-            expr = Expr::Unary(UnaryExpr {
-                operator,
-                right: Box::new(right),
-            });
+        loop {
+            if self.is_match(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.is_match(&[TokenType::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self
+                    .consume(TokenType::RightBracket, "Expect ']' after index.")?
+                    .clone();
+                expr = Expr::Index(IndexExpr {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                });
+            } else if self.is_match(&[TokenType::Dot]) {
+                let name = self
+                    .consume(TokenType::Identifier, "Expect property name after '.'.")?
+                    .clone();
+                expr = Expr::Get(GetExpr {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else {
+                break;
+            }
         }
 
         Ok(expr)
     }
 
-    fn primary(&mut self) -> Result<Expr, LoxError> {
+    fn finish_call(&mut self, callee: Expr<'src>) -> Result<Expr<'src>, LoxError> {
+        let mut arguments = Vec::new();
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    self.errors.push(Perser::error(
+                        Some(self.peek().clone()),
+                        format!("Can't have more than {MAX_ARGUMENTS} arguments."),
+                    ));
+                }
+
+                arguments.push(self.assignment()?);
+                if !self.is_match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        let paren = self
+            .consume(TokenType::RightParen, "Expect ')' after arguments.")?
+            .clone();
+
+        Ok(Expr::Call(CallExpr {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        }))
+    }
+
+    fn primary(&mut self) -> Result<Expr<'src>, LoxError> {
         if self.is_match(&[TokenType::False]) {
             return Ok(Expr::Literal(LiteralExpr {
                 value: Some(Object::False),
@@ -131,63 +571,187 @@ impl<'a> Perser<'a> {
 
         if self.is_match(&[TokenType::Number, TokenType::String]) {
             return Ok(Expr::Literal(LiteralExpr {
-                value: self.previous().literal,
+                value: self.previous().literal.clone(),
+            }));
+        }
+
+        if self.is_match(&[TokenType::Super]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
+            let method = self
+                .consume(TokenType::Identifier, "Expect superclass method name.")?
+                .clone();
+            return Ok(Expr::Super(SuperExpr { keyword, method }));
+        }
+
+        if self.is_match(&[TokenType::This]) {
+            return Ok(Expr::This(ThisExpr {
+                keyword: self.previous().clone(),
             }));
         }
 
+        if self.is_match(&[TokenType::Identifier]) {
+            return Ok(Expr::Variable(VariableExpr {
+                name: self.previous().clone(),
+            }));
+        }
+
+        if self.is_match(&[TokenType::Interpolation]) {
+            return self.interpolation();
+        }
+
+        // The scanner only produces brackets when the dialect allows them.
+        if self.is_match(&[TokenType::LeftBracket]) {
+            return self.list();
+        }
+
+        if self.dialect.maps && self.is_match(&[TokenType::LeftBrace]) {
+            return self.map();
+        }
+
         if self.is_match(&[TokenType::LeftParen]) {
-            if self.consume(TokenType::RightParen).is_some() {
-                let expr = Expr::Grouping(GroupingExpr {
-                    expression: Box::new(self.expression()?),
-                });
+            let expr = self.expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+
+            return Ok(Expr::Grouping(GroupingExpr {
+                expression: Box::new(expr),
+            }));
+        }
+
+        Err(Perser::error(
+            Some(self.peek().clone()),
+            "Expect an expression".to_string(),
+        ))
+    }
+
+    fn list(&mut self) -> Result<Expr<'src>, LoxError> {
+        let mut elements = Vec::new();
+        if !self.check(&TokenType::RightBracket) {
+            loop {
+                elements.push(self.assignment()?);
+                if !self.is_match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+        Ok(Expr::List(ListExpr { elements }))
+    }
 
-                return Ok(expr);
+    fn map(&mut self) -> Result<Expr<'src>, LoxError> {
+        let brace = self.previous().clone();
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        if !self.check(&TokenType::RightBrace) {
+            loop {
+                keys.push(self.assignment()?);
+                self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+                values.push(self.assignment()?);
+                if !self.is_match(&[TokenType::Comma]) {
+                    break;
+                }
             }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+        Ok(Expr::Map(MapExpr {
+            brace,
+            keys,
+            values,
+        }))
+    }
+
+    // The scanner splits "a ${b} c" into an `Interpolation` token for each
+    // fragment ending in "${", the tokens of each embedded expression, and a
+    // closing `String` token. Empty fragments are dropped.
+    //
+    // Fragments after the first start with the `}` that closes the previous
+    // expression, which tells them apart from strings nested inside it.
+    fn interpolation(&mut self) -> Result<Expr<'src>, LoxError> {
+        let mut parts = Vec::new();
+        loop {
+            self.fragment(&mut parts);
+            if self.closes_interpolation() {
+                return Err(Perser::error(
+                    Some(self.peek().clone()),
+                    "Expect an expression".to_string(),
+                ));
+            }
+            parts.push(self.expression()?);
 
-            return Err(Perser::error(
-                None,
-                "Expect ')' after expression.".to_string(),
-            ));
+            if !self.closes_interpolation() {
+                return Err(Perser::error(
+                    Some(self.peek().clone()),
+                    "Expect '}' after interpolated expression.".to_string(),
+                ));
+            }
+
+            if self.is_match(&[TokenType::Interpolation]) {
+                continue;
+            }
+            self.advance();
+
+            self.fragment(&mut parts);
+            return Ok(Expr::Interpolation(InterpolationExpr { parts }));
         }
+    }
 
-        Err(Perser::error(None, "Expect an expression".to_string()))
+    fn closes_interpolation(&self) -> bool {
+        let token = self.peek();
+        matches!(token.ttype, TokenType::String | TokenType::Interpolation)
+            && token.lexeme.starts_with('}')
     }
 
-    fn consume(&mut self, ttype: TokenType) -> Option<Token> {
+    fn fragment(&self, parts: &mut Vec<Expr<'src>>) {
+        match &self.previous().literal {
+            Some(Object::Str(s)) if s.is_empty() => {}
+            value => parts.push(Expr::Literal(LiteralExpr {
+                value: value.clone(),
+            })),
+        }
+    }
+
+    fn consume(&mut self, ttype: TokenType, msg: &str) -> Result<&'a Token<'src>, LoxError> {
         if self.check(&ttype) {
-            return Some(self.advance());
+            return Ok(self.advance());
         }
 
-        None
+        Err(Perser::error(Some(self.peek().clone()), msg.to_string()))
     }
 
-    fn error(token: Option<Token>, msg: String) -> LoxError {
+    fn error(token: Option<Token<'_>>, msg: String) -> LoxError {
         LoxError::error(token, msg)
     }
 
-    // fn synchronize(&mut self) {
-    //     if self.previous().is(TokenType::Semicolon) {
-    //         return;
-    //     }
-
-    //     while !self.is_at_end() {
-    //         if matches!(
-    //             self.peek().ttype,
-    //             TokenType::Class
-    //                 | TokenType::Fun
-    //                 | TokenType::Var
-    //                 | TokenType::For
-    //                 | TokenType::If
-    //                 | TokenType::While
-    //                 | TokenType::Print
-    //                 | TokenType::Return
-    //         ) {
-    //             return;
-    //         }
-
-    //         self.advance();
-    //     }
-    // }
+    // Discards tokens until the start of the next statement: just past a
+    // `;`, or at a keyword that begins a declaration or statement.
+    fn synchronize(&mut self) {
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().is(&TokenType::Semicolon) {
+                return;
+            }
+
+            if matches!(
+                self.peek().ttype,
+                TokenType::Class
+                    | TokenType::Fun
+                    | TokenType::Var
+                    | TokenType::Let
+                    | TokenType::For
+                    | TokenType::If
+                    | TokenType::While
+                    | TokenType::Print
+                    | TokenType::Return
+            ) {
+                return;
+            }
+
+            self.advance();
+        }
+    }
 
     fn is_match(&mut self, ttypes: &[TokenType]) -> bool {
         for ttype in ttypes {
@@ -199,7 +763,7 @@ impl<'a> Perser<'a> {
         false
     }
 
-    fn check(&mut self, ttype: &TokenType) -> bool {
+    fn check(&self, ttype: &TokenType) -> bool {
         if self.is_at_end() {
             return false;
         }
@@ -207,11 +771,11 @@ impl<'a> Perser<'a> {
         self.peek().is(ttype)
     }
 
-    fn is_at_end(&mut self) -> bool {
+    fn is_at_end(&self) -> bool {
         self.peek().is(&TokenType::Eof)
     }
 
-    fn advance(&mut self) -> Token {
+    fn advance(&mut self) -> &'a Token<'src> {
         if !self.is_at_end() {
             self.current += 1;
         }
@@ -219,11 +783,11 @@ impl<'a> Perser<'a> {
         self.previous()
     }
 
-    fn peek(&mut self) -> Token {
-        self.tokens[self.current].clone()
+    fn peek(&self) -> &'a Token<'src> {
+        &self.tokens[self.current]
     }
 
-    fn previous(&mut self) -> Token {
-        self.tokens[self.current - 1].clone()
+    fn previous(&self) -> &'a Token<'src> {
+        &self.tokens[self.current - 1]
     }
 }
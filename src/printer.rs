@@ -1,18 +1,24 @@
 
 use crate::error::*;
 use crate::expr::*;
+use crate::stmt::*;
 use crate::token_type::*;
 use crate::tokens::*;
 use crate::object::*;
+use crate::span::*;
 
 pub struct AstPrinter;
 
 impl AstPrinter {
-    pub fn print(&self, expr: &Expr) -> Result<String, LoxError> {
+    pub fn print(&self, expr: &Expr<'_>) -> Result<String, LoxError> {
         expr.eccept(self)
     }
 
-    pub fn parenthesize(&self, lexeme: &String, exprs: &[&Expr]) -> Result<String, LoxError> {
+    pub fn print_stmt(&self, stmt: &Stmt<'_>) -> Result<String, LoxError> {
+        stmt.eccept(self)
+    }
+
+    pub fn parenthesize(&self, lexeme: &str, exprs: &[&Expr<'_>]) -> Result<String, LoxError> {
         let mut builder = format!("({lexeme}");
         for expr in exprs {
             builder = format!("{builder} {}", expr.eccept(self)?);
@@ -21,15 +27,78 @@ impl AstPrinter {
 
         Ok(builder)
     }
+
+    fn declaration(
+        &self,
+        keyword: &str,
+        name: &Token<'_>,
+        initializer: &Option<Box<Expr<'_>>>,
+    ) -> Result<String, LoxError> {
+        match initializer {
+            Some(value) => Ok(format!(
+                "({keyword} {} = {})",
+                name.lexeme,
+                value.eccept(self)?
+            )),
+            None => Ok(format!("({keyword} {})", name.lexeme)),
+        }
+    }
 }
 
 impl ExprVisitor<String> for AstPrinter {
-    fn visitor_binary_expr(&self, expr: &BinaryExpr) -> Result<String, LoxError> {
+    fn visitor_assign_expr(&self, expr: &AssignExpr<'_>) -> Result<String, LoxError> {
+        Ok(format!(
+            "(= {} {})",
+            expr.name.lexeme,
+            expr.value.eccept(self)?
+        ))
+    }
+
+    fn visitor_binary_expr(&self, expr: &BinaryExpr<'_>) -> Result<String, LoxError> {
         self.parenthesize(&expr.operator.lexeme, &[&expr.left, &expr.right])
     }
 
-    fn visitor_grouping_expr(&self, expr: &GroupingExpr) -> Result<String, LoxError> {
-        self.parenthesize(&"group".to_string(), &[&expr.expression])
+    fn visitor_call_expr(&self, expr: &CallExpr<'_>) -> Result<String, LoxError> {
+        let mut exprs: Vec<&Expr<'_>> = vec![&expr.callee];
+        exprs.extend(expr.arguments.iter());
+        self.parenthesize("call", &exprs)
+    }
+
+    fn visitor_comma_expr(&self, expr: &CommaExpr<'_>) -> Result<String, LoxError> {
+        self.parenthesize(",", &[&expr.left, &expr.right])
+    }
+
+    fn visitor_conditional_expr(&self, expr: &ConditionalExpr<'_>) -> Result<String, LoxError> {
+        self.parenthesize(
+            "?:",
+            &[&expr.condition, &expr.then_branch, &expr.else_branch],
+        )
+    }
+
+    fn visitor_get_expr(&self, expr: &GetExpr<'_>) -> Result<String, LoxError> {
+        Ok(format!(
+            "(. {} {})",
+            expr.object.eccept(self)?,
+            expr.name.lexeme
+        ))
+    }
+
+    fn visitor_grouping_expr(&self, expr: &GroupingExpr<'_>) -> Result<String, LoxError> {
+        self.parenthesize("group", &[&expr.expression])
+    }
+    fn visitor_index_expr(&self, expr: &IndexExpr<'_>) -> Result<String, LoxError> {
+        self.parenthesize("[]", &[&expr.object, &expr.index])
+    }
+    fn visitor_index_set_expr(&self, expr: &IndexSetExpr<'_>) -> Result<String, LoxError> {
+        self.parenthesize("[]=", &[&expr.object, &expr.index, &expr.value])
+    }
+    fn visitor_interpolation_expr(&self, expr: &InterpolationExpr<'_>) -> Result<String, LoxError> {
+        let parts: Vec<&Expr<'_>> = expr.parts.iter().collect();
+        self.parenthesize("interpolate", &parts)
+    }
+    fn visitor_list_expr(&self, expr: &ListExpr<'_>) -> Result<String, LoxError> {
+        let elements: Vec<&Expr<'_>> = expr.elements.iter().collect();
+        self.parenthesize("list", &elements)
     }
     fn visitor_literal_expr(&self, expr: &LiteralExpr) -> Result<String, LoxError> {
         if let Some(value) = &expr.value {
@@ -38,9 +107,107 @@ impl ExprVisitor<String> for AstPrinter {
             Ok("nil".to_string())
         }
     }
-    fn visitor_unary_expr(&self, expr: &UnaryExpr) -> Result<String, LoxError> {
+    fn visitor_logical_expr(&self, expr: &LogicalExpr<'_>) -> Result<String, LoxError> {
+        self.parenthesize(&expr.operator.lexeme, &[&expr.left, &expr.right])
+    }
+    fn visitor_map_expr(&self, expr: &MapExpr<'_>) -> Result<String, LoxError> {
+        let mut entries: Vec<&Expr<'_>> = Vec::new();
+        for (key, value) in expr.keys.iter().zip(&expr.values) {
+            entries.push(key);
+            entries.push(value);
+        }
+        self.parenthesize("map", &entries)
+    }
+    fn visitor_set_expr(&self, expr: &SetExpr<'_>) -> Result<String, LoxError> {
+        Ok(format!(
+            "(= {} {} {})",
+            expr.object.eccept(self)?,
+            expr.name.lexeme,
+            expr.value.eccept(self)?
+        ))
+    }
+    fn visitor_super_expr(&self, expr: &SuperExpr<'_>) -> Result<String, LoxError> {
+        Ok(format!("(super {})", expr.method.lexeme))
+    }
+    fn visitor_this_expr(&self, _expr: &ThisExpr<'_>) -> Result<String, LoxError> {
+        Ok("this".to_string())
+    }
+    fn visitor_unary_expr(&self, expr: &UnaryExpr<'_>) -> Result<String, LoxError> {
         self.parenthesize(&expr.operator.lexeme, &[&expr.right])
     }
+    fn visitor_variable_expr(&self, expr: &VariableExpr<'_>) -> Result<String, LoxError> {
+        Ok(expr.name.lexeme.to_string())
+    }
+}
+
+impl StmtVisitor<String> for AstPrinter {
+    fn visitor_block_stmt(&self, stmt: &BlockStmt<'_>) -> Result<String, LoxError> {
+        let mut builder = "(block".to_string();
+        for statement in &stmt.statements {
+            builder = format!("{builder} {}", statement.eccept(self)?);
+        }
+        builder = format!("{builder})");
+
+        Ok(builder)
+    }
+    fn visitor_class_stmt(&self, stmt: &ClassStmt<'_>) -> Result<String, LoxError> {
+        let mut builder = format!("(class {}", stmt.name.lexeme);
+        if let Some(superclass) = &stmt.superclass {
+            builder = format!("{builder} < {}", superclass.name.lexeme);
+        }
+        for method in &stmt.methods {
+            builder = format!("{builder} {}", self.visitor_function_stmt(method)?);
+        }
+        builder = format!("{builder})");
+
+        Ok(builder)
+    }
+    fn visitor_expression_stmt(&self, stmt: &ExpressionStmt<'_>) -> Result<String, LoxError> {
+        self.parenthesize(";", &[&stmt.expression])
+    }
+    fn visitor_function_stmt(&self, stmt: &FunctionStmt<'_>) -> Result<String, LoxError> {
+        let params: Vec<&str> = stmt.params.iter().map(|p| p.lexeme.as_ref()).collect();
+        let mut builder = format!("(fun {}({})", stmt.name.lexeme, params.join(" "));
+        for statement in &stmt.body {
+            builder = format!("{builder} {}", statement.eccept(self)?);
+        }
+        builder = format!("{builder})");
+
+        Ok(builder)
+    }
+    fn visitor_if_stmt(&self, stmt: &IfStmt<'_>) -> Result<String, LoxError> {
+        let condition = stmt.condition.eccept(self)?;
+        let then_branch = stmt.then_branch.eccept(self)?;
+        match &stmt.else_branch {
+            Some(else_branch) => Ok(format!(
+                "(if-else {condition} {then_branch} {})",
+                else_branch.eccept(self)?
+            )),
+            None => Ok(format!("(if {condition} {then_branch})")),
+        }
+    }
+    fn visitor_let_stmt(&self, stmt: &LetStmt<'_>) -> Result<String, LoxError> {
+        self.declaration("let", &stmt.name, &stmt.initializer)
+    }
+    fn visitor_print_stmt(&self, stmt: &PrintStmt<'_>) -> Result<String, LoxError> {
+        self.parenthesize("print", &[&stmt.expression])
+    }
+    fn visitor_return_stmt(&self, stmt: &ReturnStmt<'_>) -> Result<String, LoxError> {
+        match &stmt.value {
+            Some(value) => self.parenthesize("return", &[value]),
+            None => Ok("(return)".to_string()),
+        }
+    }
+    fn visitor_var_stmt(&self, stmt: &VarStmt<'_>) -> Result<String, LoxError> {
+        self.declaration("var", &stmt.name, &stmt.initializer)
+    }
+    fn visitor_while_stmt(&self, stmt: &WhileStmt<'_>) -> Result<String, LoxError> {
+        Ok(format!(
+            "(while {} {})",
+            stmt.condition.eccept(self)?,
+            stmt.body.eccept(self)?
+        ))
+    }
 }
 
 pub fn _print() {
@@ -48,9 +215,12 @@ pub fn _print() {
         left: Box::new(Expr::Unary(UnaryExpr {
             operator: Token {
                 ttype: TokenType::Minus,
-                lexeme: "-".to_string(),
+                lexeme: "-".into(),
                 literal: None,
                 line: 1,
+                span: Span::default(),
+                leading_trivia: Vec::new(),
+                trailing_trivia: Vec::new(),
             },
             right: Box::new(Expr::Literal(LiteralExpr {
                 value: Some(Object::Num(123.0)),
@@ -58,9 +228,12 @@ pub fn _print() {
         })),
         operator: Token {
             ttype: TokenType::Star,
-            lexeme: "*".to_string(),
+            lexeme: "*".into(),
             literal: None,
             line: 1,
+            span: Span::default(),
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
         },
         right: Box::new(Expr::Grouping(GroupingExpr {
             expression: Box::new(Expr::Literal(LiteralExpr {
@@ -1,105 +1,834 @@
+use crate::dialect::*;
 use crate::error::*;
 use crate::token_type::*;
 use crate::tokens::*;
 use crate::object::*;
+use crate::source_map::*;
+use crate::span::*;
+use crate::trivia::*;
 
-#[derive(Debug)]
-pub struct Scanner {
-    source: Vec<char>,
-    tokens: Vec<Token>,
-    start: usize,
-    current: usize,
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::ops::Range;
+
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+enum Input<'src> {
+    // The whole source is in memory, so lexemes borrow straight from it.
+    Str(&'src str),
+    // Only the current lexeme and one read-ahead chunk are buffered.
+    // `base` is the absolute byte offset of `buf[0]`.
+    Reader {
+        reader: Box<dyn Read + 'src>,
+        buf: Vec<u8>,
+        base: usize,
+        done: bool,
+    },
+}
+
+/// A text edit: the bytes in `range` of the old source were replaced by
+/// `text`.
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Lexes Lox source as a stream of tokens.
+///
+/// Scanning a `&str` is zero-copy: every token's lexeme borrows from the
+/// source. Scanning an `io::Read` only buffers the lexeme being scanned
+/// plus one read-ahead chunk, so tokens can be consumed one at a time
+/// through the `Iterator` impl without holding the whole program in memory.
+pub struct Scanner<'src> {
+    input: Input<'src>,
+    dialect: Dialect,
+    file: FileId,
+    pending: VecDeque<Result<Token<'src>, LoxError>>,
+    done: bool,
+    tokens: Vec<Token<'src>>,
+    errors: Vec<LoxError>,
+    lossless: bool,
+    leading_trivia: Vec<Trivia<'src>>,
+    // Token still collecting trailing trivia in lossless mode.
+    last: Option<Token<'src>>,
+    // Open '{' count inside each "${" being scanned, innermost last.
+    interpolations: Vec<usize>,
     line: usize,
+    column: usize,
+    offset: usize,
+    start_line: usize,
+    start_column: usize,
+    start_offset: usize,
 }
 
-impl Scanner {
-    pub fn new(source: String) -> Scanner {
+impl<'src> Scanner<'src> {
+    pub fn new(source: &'src str, dialect: Dialect) -> Scanner<'src> {
+        Scanner::with_input(Input::Str(source), dialect)
+    }
+
+    pub fn from_reader<R: Read + 'src>(reader: R, dialect: Dialect) -> Scanner<'src> {
+        let input = Input::Reader {
+            reader: Box::new(reader),
+            buf: Vec::new(),
+            base: 0,
+            done: false,
+        };
+        Scanner::with_input(input, dialect)
+    }
+
+    fn with_input(input: Input<'src>, dialect: Dialect) -> Scanner<'src> {
         Scanner {
-            source: source.chars().collect(),
+            input,
+            dialect,
+            file: FileId::default(),
+            pending: VecDeque::new(),
+            done: false,
             tokens: Vec::new(),
-            start: 0,
-            current: 0,
+            errors: Vec::new(),
+            lossless: false,
+            leading_trivia: Vec::new(),
+            last: None,
+            interpolations: Vec::new(),
             line: 1,
+            column: 1,
+            offset: 0,
+            start_line: 1,
+            start_column: 1,
+            start_offset: 0,
+        }
+    }
+
+    /// Keeps whitespace, newlines and comments as trivia on the tokens
+    /// instead of dropping them. Trivia on the same line after a token is
+    /// its trailing trivia; everything else leads the next token (or EOF).
+    pub fn lossless(mut self) -> Scanner<'src> {
+        self.lossless = true;
+        self
+    }
+
+    /// Tags every span with the `SourceMap` file the source came from.
+    pub fn file(mut self, file: FileId) -> Scanner<'src> {
+        self.file = file;
+        self
+    }
+
+    /// Scans the whole source. Lexical errors don't stop the scan: each one
+    /// produces a `TokenType::Error` token and is collected alongside the
+    /// tokens, so callers can report every problem at once.
+    pub fn scan_tokens(&mut self) -> (&Vec<Token<'src>>, &Vec<LoxError>) {
+        while let Some(item) = self.next() {
+            match item {
+                Ok(token) => self.tokens.push(token),
+                Err(err) => self.errors.push(err),
+            }
+        }
+
+        (&self.tokens, &self.errors)
+    }
+
+    /// Re-lexes the scanner's source after `edit`, given the tokens of the
+    /// source before the edit. Only the damaged region is scanned: lexing
+    /// restarts a token before the edit and stops as soon as it produces a
+    /// token identical to an old one, whose tail is reused with shifted
+    /// positions. The old errors outside the re-lexed region are kept (and
+    /// shifted) the same way, so the result covers the whole source.
+    ///
+    /// `old` and `old_errors` must come from a scanner with the same
+    /// configuration. Input read from an `io::Read` can't be rewound, so it
+    /// is scanned in full.
+    pub fn relex(
+        mut self,
+        old: &[Token<'_>],
+        old_errors: &[LoxError],
+        edit: &Edit,
+    ) -> (Vec<Token<'src>>, Vec<LoxError>) {
+        let Input::Str(source) = self.input else {
+            self.scan_tokens();
+            return (self.tokens, self.errors);
+        };
+
+        // Interpolated strings make lexing depend on the tokens before, so
+        // only restart (and resync) where no interpolation is open.
+        let mut stack = Vec::new();
+        let mut outside_interpolation = Vec::with_capacity(old.len());
+        for token in old {
+            outside_interpolation.push(stack.is_empty());
+            track_interpolation(&mut stack, token);
+        }
+
+        // Step back one extra token since a token's lexeme can depend on the
+        // characters after it, e.g. `1.` followed by an inserted `5`.
+        let damaged = old
+            .iter()
+            .position(|token| full_end(token) >= edit.range.start)
+            .unwrap_or(old.len());
+        let mut restart = damaged.saturating_sub(2);
+        while restart > 0 && !outside_interpolation[restart] {
+            restart -= 1;
+        }
+        // Without trivia a token starts after the whitespace and comments in
+        // front of it, which the edit may be in. Rescan from the top then.
+        if old
+            .get(restart)
+            .is_some_and(|token| full_start(token) > edit.range.start)
+        {
+            restart = 0;
+        }
+
+        let mut tokens: Vec<Token<'src>> = old[..restart]
+            .iter()
+            .map(|token| rebind(source, token, 0, 0, 0, 0))
+            .collect();
+
+        if let Some(token) = old.get(restart).filter(|_| restart > 0) {
+            let start = token.leading_trivia.first().map_or(token.span, |t| t.span);
+            self.offset = start.start;
+            self.line = start.start_line;
+            self.column = start.start_col;
         }
+
+        // Errors always lie inside the token they were reported for.
+        let starts_before =
+            |err: &LoxError, offset: usize| err.span().is_none_or(|s| s.start < offset);
+        let resume = self.offset;
+        let mut errors: Vec<LoxError> = old_errors
+            .iter()
+            .filter(|err| starts_before(err, resume))
+            .cloned()
+            .collect();
+
+        let delta = edit.text.len() as isize - edit.range.len() as isize;
+        let edit_end = edit.range.start + edit.text.len();
+        let mut stack = Vec::new();
+        for item in self.by_ref() {
+            let token = match item {
+                Ok(token) => token,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+
+            let start = full_start(&token);
+            if start >= edit_end && stack.is_empty() {
+                let target = (start as isize - delta) as usize;
+                if let Ok(k) = old.binary_search_by_key(&target, full_start) {
+                    if outside_interpolation[k] && same_token(&old[k], &token) {
+                        let old_start = old[k]
+                            .leading_trivia
+                            .first()
+                            .map_or(old[k].span, |t| t.span);
+                        let new_start = token.leading_trivia.first().map_or(token.span, |t| t.span);
+                        let line = old_start.start_line;
+                        let line_delta = new_start.start_line as isize - line as isize;
+                        let col_delta = new_start.start_col as isize - old_start.start_col as isize;
+
+                        tokens.extend(
+                            old[k..]
+                                .iter()
+                                .map(|t| rebind(source, t, delta, line, line_delta, col_delta)),
+                        );
+
+                        // Errors for the matched token were already reported
+                        // by the old scan.
+                        errors.retain(|err| starts_before(err, start));
+                        errors.extend(old_errors.iter().filter_map(|err| {
+                            let span = err.span().filter(|span| span.start >= target)?;
+                            let span = shift(span, delta, line, line_delta, col_delta);
+                            Some(err.clone().with_span(span))
+                        }));
+                        return (tokens, errors);
+                    }
+                }
+            }
+
+            track_interpolation(&mut stack, &token);
+            tokens.push(token);
+        }
+
+        (tokens, errors)
     }
-    pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, LoxError> {
-        while !self.is_at_end() {
-            self.start = self.current;
-            let (ttype, literals) = self.scan_token()?;
-            match ttype {
-                TokenType::Unknown => {}
-                _ => self.add_token(ttype, literals),
+
+    // Scans one lexeme, queueing whatever it produces. Reaching the end of
+    // input queues the EOF token.
+    fn scan_next(&mut self) {
+        self.mark_start();
+        if self.is_at_end() {
+            if !self.interpolations.is_empty() {
+                self.interpolations.clear();
+                self.error(LoxError::at(
+                    self.span(),
+                    "Unterminated string interpolation".to_string(),
+                ));
+            }
+
+            self.flush();
+            let mut eof = Token::eof(self.span());
+            eof.leading_trivia = std::mem::take(&mut self.leading_trivia);
+            self.pending.push_back(Ok(eof));
+            self.done = true;
+            return;
+        }
+
+        match self.scan_token() {
+            Ok((TokenType::Unknown, _)) => self.add_trivia(),
+            Ok((ttype, literals)) => self.add_token(ttype, literals),
+            Err(err) => {
+                self.error(err);
+                self.add_token(TokenType::Error, None);
             }
         }
+    }
 
-        self.tokens.push(Token::eof(self.line));
-        Ok(&self.tokens)
+    fn error(&mut self, err: LoxError) {
+        self.flush();
+        self.pending.push_back(Err(err));
     }
 
-    fn is_at_end(&self) -> bool {
-        self.current >= self.source.len()
+    fn flush(&mut self) {
+        if let Some(token) = self.last.take() {
+            self.pending.push_back(Ok(token));
+        }
+    }
+
+    fn is_at_end(&mut self) -> bool {
+        self.byte(self.offset).is_none()
+    }
+
+    // Returns the byte at absolute offset `pos`, reading more input if needed.
+    fn byte(&mut self, pos: usize) -> Option<u8> {
+        loop {
+            match &self.input {
+                Input::Str(source) => return source.as_bytes().get(pos).copied(),
+                Input::Reader {
+                    buf, base, done, ..
+                } => {
+                    if pos < base + buf.len() || *done {
+                        return buf.get(pos - base).copied();
+                    }
+                }
+            }
+            self.refill();
+        }
+    }
+
+    fn refill(&mut self) {
+        let Input::Reader {
+            reader,
+            buf,
+            base,
+            done,
+        } = &mut self.input
+        else {
+            return;
+        };
+
+        // Bytes before the current lexeme are never looked at again.
+        buf.drain(..self.start_offset - *base);
+        *base = self.start_offset;
+
+        let len = buf.len();
+        buf.resize(len + CHUNK_SIZE, 0);
+        loop {
+            match reader.read(&mut buf[len..]) {
+                Ok(n) => {
+                    buf.truncate(len + n);
+                    *done = n == 0;
+                    return;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    buf.truncate(len);
+                    *done = true;
+                    let span = self.span_from(self.offset, self.line, self.column);
+                    self.error(LoxError::at(span, format!("Unable to read source: {e}")));
+                    return;
+                }
+            }
+        }
+    }
+
+    // Bytes from `pos` up to at most `len`, reading more input if needed.
+    fn bytes(&mut self, pos: usize, len: usize) -> &[u8] {
+        self.byte(pos + len - 1);
+        match &self.input {
+            Input::Str(source) => {
+                let bytes = source.as_bytes();
+                &bytes[pos..(pos + len).min(bytes.len())]
+            }
+            Input::Reader { buf, base, .. } => &buf[pos - base..(pos + len - base).min(buf.len())],
+        }
+    }
+
+    // Decodes the character starting at byte offset `pos` along with its
+    // width in bytes. Invalid UTF-8 decodes to U+FFFD one byte at a time.
+    fn char_at(&mut self, pos: usize) -> Option<(char, usize)> {
+        if let Input::Str(source) = &self.input {
+            let c = source.get(pos..)?.chars().next()?;
+            return Some((c, c.len_utf8()));
+        }
+
+        let first = self.byte(pos)?;
+        let width = match first {
+            0x00..=0x7f => return Some((first as char, 1)),
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Some((char::REPLACEMENT_CHARACTER, 1)),
+        };
+        match std::str::from_utf8(self.bytes(pos, width)) {
+            Ok(s) => s.chars().next().map(|c| (c, width)),
+            Err(_) => Some((char::REPLACEMENT_CHARACTER, 1)),
+        }
     }
 
     fn advance(&mut self) -> char {
-        let result = self.source[self.current];
-        self.current += 1;
+        let (result, width) = self.char_at(self.offset).unwrap_or(('\0', 0));
+        self.offset += width;
+        if result == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
         result
     }
 
+    fn mark_start(&mut self) {
+        self.start_line = self.line;
+        self.start_column = self.column;
+        self.start_offset = self.offset;
+    }
+
+    fn span(&self) -> Span {
+        self.span_from(self.start_offset, self.start_line, self.start_column)
+    }
+
+    fn span_from(&self, start: usize, line: usize, column: usize) -> Span {
+        Span {
+            file: self.file,
+            start,
+            end: self.offset,
+            start_line: line,
+            start_col: column,
+            end_line: self.line,
+            end_col: self.column,
+        }
+    }
+
+    fn lexeme(&self) -> Cow<'src, str> {
+        match &self.input {
+            Input::Str(source) => Cow::Borrowed(&source[self.start_offset..self.offset]),
+            Input::Reader { buf, base, .. } => {
+                let bytes = &buf[self.start_offset - base..self.offset - base];
+                Cow::Owned(String::from_utf8_lossy(bytes).into_owned())
+            }
+        }
+    }
+
     fn add_token(&mut self, ttype: TokenType, literals: Option<Object>) {
-        let lexeme = self.source[self.start..self.current].iter().collect();
+        let lexeme = self.lexeme();
+        let mut token = Token::new(ttype, lexeme, literals, self.span());
+        token.leading_trivia = std::mem::take(&mut self.leading_trivia);
 
-        self.tokens
-            .push(Token::new(ttype, lexeme, literals, self.line));
+        self.flush();
+        if self.lossless {
+            self.last = Some(token);
+        } else {
+            self.pending.push_back(Ok(token));
+        }
+    }
+
+    fn add_trivia(&mut self) {
+        if !self.lossless {
+            return;
+        }
+
+        let text = self.lexeme();
+        let kind = if text.starts_with('\n') {
+            TriviaKind::Newline
+        } else if text.starts_with("//") {
+            TriviaKind::LineComment
+        } else if text.starts_with("/*") {
+            TriviaKind::BlockComment
+        } else {
+            TriviaKind::Whitespace
+        };
+        let trivia = Trivia {
+            kind,
+            text,
+            span: self.span(),
+        };
+
+        match &mut self.last {
+            Some(token) if kind != TriviaKind::Newline => token.trailing_trivia.push(trivia),
+            _ => {
+                self.flush();
+                self.leading_trivia.push(trivia);
+            }
+        }
     }
 
     fn is_match(&mut self, expected: char) -> Option<()> {
-        if self.source[self.current] != expected || self.is_at_end() {
+        if self.is_at_end() || self.peek() != expected {
             return None;
         }
 
-        self.current += 1;
+        self.advance();
         Some(())
     }
 
-    fn string(&mut self) -> Result<Object, LoxError> {
+    // Scans a string literal, or the next fragment of one that contains
+    // interpolations. A fragment ending in "${" becomes an `Interpolation`
+    // token; the expression tokens follow, and the matching '}' resumes the
+    // string where it left off.
+    fn string(&mut self) -> Result<(TokenType, Option<Object>), LoxError> {
+        let mut val = String::new();
+        let mut valid = true;
+        let mut ttype = TokenType::String;
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            if self.dialect.extended_literals && self.peek() == '$' && self.peek_next() == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push(0);
+                ttype = TokenType::Interpolation;
+                break;
+            }
+
+            match self.advance() {
+                // Keep going after a bad escape so the rest of the literal
+                // is consumed and every bad escape in it gets reported.
+                '\\' if self.dialect.extended_literals && !self.is_at_end() => {
+                    match self.escape() {
+                        Ok(c) => val.push(c),
+                        Err(err) => {
+                            self.error(err);
+                            valid = false;
+                        }
+                    }
+                }
+                c => val.push(c),
             }
+        }
+
+        if ttype == TokenType::String {
+            if self.is_at_end() {
+                return Err(LoxError::at(self.span(), "Unterminated string".to_string()));
+            }
+
             self.advance();
         }
 
-        if self.is_at_end() {
-            return Err(LoxError::error(None, "Unterminated string".to_string()));
+        if valid {
+            Ok((ttype, Some(Object::Str(val))))
+        } else {
+            Ok((TokenType::Error, None))
+        }
+    }
+
+    // Raw strings take every character literally: r"C:\path". Any number
+    // of '#'s can fence a raw string that contains quotes: r#"say "hi""#.
+    fn raw_string(&mut self) -> Result<(TokenType, Option<Object>), LoxError> {
+        let mut hashes = 0;
+        while self.is_match('#').is_some() {
+            hashes += 1;
+        }
+
+        if self.is_match('"').is_none() {
+            return Err(LoxError::at(
+                self.span(),
+                "Expect '\"' to start raw string".to_string(),
+            ));
         }
 
+        let mut val = String::new();
+        loop {
+            if self.is_at_end() {
+                return Err(LoxError::at(
+                    self.span(),
+                    "Unterminated raw string".to_string(),
+                ));
+            }
+
+            let c = self.advance();
+            if c == '"' && self.is_fenced(hashes) {
+                for _ in 0..hashes {
+                    self.advance();
+                }
+                return Ok((TokenType::String, Some(Object::Str(val))));
+            }
+            val.push(c);
+        }
+    }
+
+    fn is_fenced(&mut self, hashes: usize) -> bool {
+        (0..hashes).all(|i| self.byte(self.offset + i) == Some(b'#'))
+    }
+
+    // Triple-quoted strings can span lines and have their common indentation
+    // stripped, so they can be indented along with the surrounding code:
+    //
+    //     var sql = """
+    //         select *
+    //         from users
+    //         """;
+    //
+    // A line break right after the opening quotes is dropped, as is a
+    // closing line holding nothing but indentation, which still counts
+    // towards the common indentation. Escapes are decoded; "${" is literal.
+    fn multiline_string(&mut self) -> Result<(TokenType, Option<Object>), LoxError> {
         self.advance();
-        let val: String = self.source[self.start + 1..self.current - 1]
-            .iter()
-            .collect();
+        self.advance();
+
+        // Each line's decoded text and the width of its literal indentation.
+        let mut lines = vec![(String::new(), 0)];
+        let mut indenting = true;
+        let mut valid = true;
+        loop {
+            if self.is_at_end() {
+                return Err(LoxError::at(self.span(), "Unterminated string".to_string()));
+            }
+
+            if self.peek() == '"'
+                && self.peek_next() == '"'
+                && self.byte(self.offset + 2) == Some(b'"')
+            {
+                self.advance();
+                self.advance();
+                self.advance();
+                break;
+            }
+
+            let (text, indent) = lines.last_mut().unwrap();
+            match self.advance() {
+                '\n' => {
+                    lines.push((String::new(), 0));
+                    indenting = true;
+                }
+                c @ (' ' | '\t') if indenting => {
+                    text.push(c);
+                    *indent += 1;
+                }
+                '\\' if !self.is_at_end() => {
+                    indenting = false;
+                    match self.escape() {
+                        Ok(c) => lines.last_mut().unwrap().0.push(c),
+                        Err(err) => {
+                            self.error(err);
+                            valid = false;
+                        }
+                    }
+                }
+                c => {
+                    indenting = false;
+                    text.push(c);
+                }
+            }
+        }
+
+        if !valid {
+            return Ok((TokenType::Error, None));
+        }
+        Ok((
+            TokenType::String,
+            Some(Object::Str(strip_indentation(lines))),
+        ))
+    }
 
-        Ok(Object::Str(val))
+    fn left_brace(&mut self) -> Result<(TokenType, Option<Object>), LoxError> {
+        if let Some(depth) = self.interpolations.last_mut() {
+            *depth += 1;
+        }
+        Ok((TokenType::LeftBrace, None))
     }
 
-    fn number(&mut self) -> Result<Object, LoxError> {
-        while self.peek().is_ascii_digit() {
-            self.advance();
+    fn right_brace(&mut self) -> Result<(TokenType, Option<Object>), LoxError> {
+        match self.interpolations.last_mut() {
+            Some(0) => {
+                self.interpolations.pop();
+                self.string()
+            }
+            Some(depth) => {
+                *depth -= 1;
+                Ok((TokenType::RightBrace, None))
+            }
+            None => Ok((TokenType::RightBrace, None)),
         }
+    }
+
+    // Decodes the escape sequence following a '\' inside a string literal.
+    fn escape(&mut self) -> Result<char, LoxError> {
+        let (start, line, column) = (self.offset - 1, self.line, self.column - 1);
+        match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '0' => Ok('\0'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(start, line, column),
+            c => Err(LoxError::at(
+                self.span_from(start, line, column),
+                format!("Unknown escape sequence '\\{c}'"),
+            )),
+        }
+    }
+
+    fn unicode_escape(
+        &mut self,
+        start: usize,
+        line: usize,
+        column: usize,
+    ) -> Result<char, LoxError> {
+        if self.is_match('{').is_none() {
+            return Err(LoxError::at(
+                self.span_from(start, line, column),
+                "Expect '{' after '\\u'".to_string(),
+            ));
+        }
+
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            digits.push(self.advance());
+        }
+
+        if self.is_match('}').is_none() {
+            return Err(LoxError::at(
+                self.span_from(start, line, column),
+                "Expect '}' after unicode escape digits".to_string(),
+            ));
+        }
+
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(LoxError::at(
+                self.span_from(start, line, column),
+                "Unicode escape must have 1 to 6 hex digits".to_string(),
+            ));
+        }
+
+        let code = u32::from_str_radix(&digits, 16).unwrap_or(u32::MAX);
+        char::from_u32(code).ok_or_else(|| {
+            LoxError::at(
+                self.span_from(start, line, column),
+                format!("Invalid unicode code point '\\u{{{digits}}}'"),
+            )
+        })
+    }
+
+    fn number(&mut self, c: char) -> Result<Object, LoxError> {
+        let result = self.number_literal(c);
+        if result.is_err() {
+            // Swallow the rest of a malformed literal so it doesn't cascade
+            // into a string of unrelated errors.
+            while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+                self.advance();
+            }
+        }
+        result
+    }
+
+    fn number_literal(&mut self, c: char) -> Result<Object, LoxError> {
+        if c == '0' && self.dialect.extended_literals {
+            let radix = match self.peek() {
+                'x' | 'X' => Some((16, "hexadecimal")),
+                'o' | 'O' => Some((8, "octal")),
+                'b' | 'B' => Some((2, "binary")),
+                _ => None,
+            };
+            if let Some((radix, name)) = radix {
+                self.advance();
+                return self.radix_number(radix, name);
+            }
+        }
+
+        let mut val = c.to_string();
+        self.digits(10, &mut val)?;
 
         // Look for a fractional part.
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             // Consume the "."
-            self.advance();
-            while self.peek().is_ascii_digit() {
-                self.advance();
+            val.push(self.advance());
+            self.digits(10, &mut val)?;
+        }
+
+        // Look for an exponent.
+        if self.dialect.extended_literals && matches!(self.peek(), 'e' | 'E') {
+            val.push(self.advance());
+            if matches!(self.peek(), '+' | '-') {
+                val.push(self.advance());
+            }
+            if !self.peek().is_ascii_digit() {
+                return Err(LoxError::at(
+                    self.span(),
+                    "Expect digits in number exponent".to_string(),
+                ));
             }
+            self.digits(10, &mut val)?;
         }
 
-        let val: String = self.source[self.start..self.current].iter().collect();
         match val.parse::<f64>() {
             Ok(num) => Ok(Object::Num(num)),
-            Err(_) => Err(LoxError::error(None, "Unable to parse number".to_string())),
+            Err(_) => Err(LoxError::at(
+                self.span(),
+                "Unable to parse number".to_string(),
+            )),
+        }
+    }
+
+    fn radix_number(&mut self, radix: u32, name: &str) -> Result<Object, LoxError> {
+        if !self.peek().is_digit(radix) {
+            return Err(LoxError::at(
+                self.span(),
+                format!("Expect {name} digits after '{}'", self.lexeme()),
+            ));
+        }
+
+        let mut val = String::new();
+        self.digits(radix, &mut val)?;
+        if self.peek().is_ascii_alphanumeric() {
+            let c = self.advance();
+            return Err(LoxError::at(
+                self.span(),
+                format!("Invalid digit '{c}' in {name} literal"),
+            ));
+        }
+
+        match u128::from_str_radix(&val, radix) {
+            Ok(num) => Ok(Object::Num(num as f64)),
+            Err(_) => Err(LoxError::at(
+                self.span(),
+                "Number literal is too large".to_string(),
+            )),
+        }
+    }
+
+    // Consumes digits of the given radix into `val`, skipping '_' separators.
+    // A separator has to sit between two digits, so `1__0`, `1_` and `0x_1`
+    // are all rejected.
+    fn digits(&mut self, radix: u32, val: &mut String) -> Result<(), LoxError> {
+        loop {
+            let c = self.peek();
+            if c.is_digit(radix) {
+                val.push(self.advance());
+            } else if c == '_' && self.dialect.extended_literals {
+                let after_digit = self
+                    .byte(self.offset - 1)
+                    .is_some_and(|b| (b as char).is_digit(radix));
+                self.advance();
+                if !after_digit || !self.peek().is_digit(radix) {
+                    return Err(LoxError::at(
+                        self.span(),
+                        "Digit separator '_' must be between digits".to_string(),
+                    ));
+                }
+            } else {
+                return Ok(());
+            }
         }
     }
 
@@ -109,79 +838,92 @@ impl Scanner {
                 self.advance();
             }
             Ok(TokenType::Unknown)
+        } else if self.is_match('*').is_some() {
+            self.block_comment()?;
+            Ok(TokenType::Unknown)
         } else {
             Ok(TokenType::Slash)
         }
     }
 
-    fn peek_next(&mut self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
+    // Block comments nest, so `/* a /* b */ c */` is a single comment.
+    fn block_comment(&mut self) -> Result<(), LoxError> {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                let opening = Span {
+                    file: self.file,
+                    start: self.start_offset,
+                    end: self.start_offset + 2,
+                    start_line: self.start_line,
+                    start_col: self.start_column,
+                    end_line: self.start_line,
+                    end_col: self.start_column + 2,
+                };
+                return Err(LoxError::at(
+                    opening,
+                    "Unterminated block comment".to_string(),
+                ));
+            }
+
+            match self.advance() {
+                '/' if self.is_match('*').is_some() => depth += 1,
+                '*' if self.is_match('/').is_some() => depth -= 1,
+                _ => {}
+            }
         }
-        self.source[self.current + 1]
+
+        Ok(())
     }
 
-    fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
+    fn peek_next(&mut self) -> char {
+        match self.char_at(self.offset) {
+            Some((_, width)) => self.char_at(self.offset + width).map_or('\0', |(c, _)| c),
+            None => '\0',
         }
-        self.source[self.current]
     }
 
-    fn identifier(&mut self, c: char) -> Result<TokenType, LoxError> {
-        while self.peek().is_ascii_alphanumeric() || c == '_' {
+    fn peek(&mut self) -> char {
+        self.char_at(self.offset).map_or('\0', |(c, _)| c)
+    }
+
+    // Identifiers follow Unicode UAX #31: an XID_Start character or '_',
+    // then any run of XID_Continue characters (which includes '_').
+    fn identifier(&mut self) -> Result<TokenType, LoxError> {
+        while is_xid_continue(self.peek()) {
             self.advance();
         }
 
-        let text: String = self.source[self.start..self.current].iter().collect();
-        match self.keyword(text.as_str()) {
+        let text = self.lexeme();
+        match self.dialect.keyword(&text) {
             Some(ttype) => Ok(ttype),
             None => Ok(TokenType::Identifier),
         }
     }
 
-    //TODO: Use HashMap and the keywords Globaly Static
-    fn keyword(&self, check: &str) -> Option<TokenType> {
-        match check {
-            "and" => Some(TokenType::And),
-            "class" => Some(TokenType::Class),
-            "else" => Some(TokenType::Else),
-            "false" => Some(TokenType::False),
-            "for" => Some(TokenType::For),
-            "fun" => Some(TokenType::Fun),
-            "if" => Some(TokenType::If),
-            "nil" => Some(TokenType::Nil),
-            "or" => Some(TokenType::Or),
-            "print" => Some(TokenType::Print),
-            "return" => Some(TokenType::Return),
-            "super" => Some(TokenType::Super),
-            "this" => Some(TokenType::This),
-            "true" => Some(TokenType::True),
-            "var" => Some(TokenType::Var),
-            "let" => Some(TokenType::Let),
-            "while" => Some(TokenType::While),
-            "import" => Some(TokenType::Import),
-            _ => None,
-        }
-    }
-
     fn scan_token(&mut self) -> Result<(TokenType, Option<Object>), LoxError> {
         let c = self.advance();
         match c {
-            ' ' | '\t' | '\r' => Ok((TokenType::Unknown, None)),
-            '\n' => {
-                self.line += 1;
+            ' ' | '\t' | '\r' => {
+                while matches!(self.peek(), ' ' | '\t' | '\r') {
+                    self.advance();
+                }
                 Ok((TokenType::Unknown, None))
             }
+            '\n' => Ok((TokenType::Unknown, None)),
 
             '.' => Ok((TokenType::Dot, None)),
             '(' => Ok((TokenType::LeftParen, None)),
             ')' => Ok((TokenType::RightParen, None)),
-            '{' => Ok((TokenType::LeftBrace, None)),
-            '}' => Ok((TokenType::RightBrace, None)),
-            '[' => Ok((TokenType::LeftBracket, None)),
-            ']' => Ok((TokenType::RightBracket, None)),
+            '{' => self.left_brace(),
+            '}' => self.right_brace(),
+            '[' if self.dialect.brackets => Ok((TokenType::LeftBracket, None)),
+            ']' if self.dialect.brackets => Ok((TokenType::RightBracket, None)),
             ',' => Ok((TokenType::Comma, None)),
+            ':' if self.dialect.maps || self.dialect.extended_operators => {
+                Ok((TokenType::Colon, None))
+            }
+            '?' if self.dialect.extended_operators => Ok((TokenType::Question, None)),
             '-' => Ok((TokenType::Minus, None)),
             '+' => Ok((TokenType::Plus, None)),
             ';' => Ok((TokenType::Semicolon, None)),
@@ -205,13 +947,169 @@ impl Scanner {
                 None => Ok((TokenType::Greater, None)),
             },
 
-            // TODO: handle excape sequence
-            '"' => Ok((TokenType::String, Some(self.string()?))),
-            '0'..='9' => Ok((TokenType::Number, Some(self.number()?))),
+            '"' if self.dialect.extended_literals
+                && self.peek() == '"'
+                && self.peek_next() == '"' =>
+            {
+                self.multiline_string()
+            }
+            '"' => self.string(),
+            '0'..='9' => Ok((TokenType::Number, Some(self.number(c)?))),
             '/' => Ok((self.divider('/')?, None)),
 
-            _ if c.is_ascii_alphabetic() || c == '_' => Ok((self.identifier(c)?, None)),
-            _ => Err(LoxError::error(None, "Unexpected character".to_string())),
+            'r' if self.dialect.extended_literals && matches!(self.peek(), '"' | '#') => {
+                self.raw_string()
+            }
+            _ if is_xid_start(c) || c == '_' => Ok((self.identifier()?, None)),
+            _ => Err(LoxError::at(
+                self.span(),
+                "Unexpected character".to_string(),
+            )),
         }
     }
 }
+
+impl<'src> Iterator for Scanner<'src> {
+    type Item = Result<Token<'src>, LoxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item);
+            }
+            if self.done {
+                return None;
+            }
+            self.scan_next();
+        }
+    }
+}
+
+// Joins the lines of a multi-line string, dropping the common indentation.
+// A line is blank when it is all indentation.
+fn strip_indentation(mut lines: Vec<(String, usize)>) -> String {
+    let is_blank = |(text, indent): &(String, usize)| text.chars().count() == *indent;
+
+    if lines.len() > 1 && is_blank(&lines[0]) {
+        lines.remove(0);
+    }
+    let closing = match lines.last() {
+        Some(line) if lines.len() > 1 && is_blank(line) => lines.pop(),
+        _ => None,
+    };
+
+    let common = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .chain(closing.as_ref())
+        .map(|(_, indent)| *indent)
+        .min()
+        .unwrap_or(0);
+
+    let stripped: Vec<String> = lines
+        .into_iter()
+        .map(|(text, _)| text.chars().skip(common).collect())
+        .collect();
+    stripped.join("\n")
+}
+
+fn full_start(token: &Token<'_>) -> usize {
+    token
+        .leading_trivia
+        .first()
+        .map_or(token.span.start, |t| t.span.start)
+}
+
+fn full_end(token: &Token<'_>) -> usize {
+    token
+        .trailing_trivia
+        .last()
+        .map_or(token.span.end, |t| t.span.end)
+}
+
+fn same_token(old: &Token<'_>, new: &Token<'_>) -> bool {
+    old.ttype == new.ttype
+        && old.lexeme == new.lexeme
+        && old.leading_trivia.len() == new.leading_trivia.len()
+        && full_end(old) - full_start(old) == full_end(new) - full_start(new)
+}
+
+// Mirrors the scanner's interpolation stack for an already scanned token.
+fn track_interpolation(stack: &mut Vec<usize>, token: &Token<'_>) {
+    match token.ttype {
+        TokenType::LeftBrace => {
+            if let Some(depth) = stack.last_mut() {
+                *depth += 1;
+            }
+        }
+        TokenType::RightBrace => {
+            if let Some(depth) = stack.last_mut() {
+                *depth = depth.saturating_sub(1);
+            }
+        }
+        TokenType::String | TokenType::Interpolation | TokenType::Error => {
+            if token.lexeme.starts_with('}') {
+                stack.pop();
+            }
+            if token.lexeme.ends_with("${") {
+                stack.push(0);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Moves a span by `delta` bytes and `line_delta` lines, shifting its columns
+// if it is on `line`.
+fn shift(span: Span, delta: isize, line: usize, line_delta: isize, col_delta: isize) -> Span {
+    Span {
+        file: span.file,
+        start: (span.start as isize + delta) as usize,
+        end: (span.end as isize + delta) as usize,
+        start_line: (span.start_line as isize + line_delta) as usize,
+        start_col: if span.start_line == line {
+            (span.start_col as isize + col_delta) as usize
+        } else {
+            span.start_col
+        },
+        end_line: (span.end_line as isize + line_delta) as usize,
+        end_col: if span.end_line == line {
+            (span.end_col as isize + col_delta) as usize
+        } else {
+            span.end_col
+        },
+    }
+}
+
+// Moves an old token by `delta` bytes and `line_delta` lines, shifting the
+// columns of anything on `line` (the line the move is anchored to), and
+// re-borrows its text from the new source.
+fn rebind<'src>(
+    source: &'src str,
+    token: &Token<'_>,
+    delta: isize,
+    line: usize,
+    line_delta: isize,
+    col_delta: isize,
+) -> Token<'src> {
+    let shift = |span: Span| shift(span, delta, line, line_delta, col_delta);
+    let trivia = |trivia: &Trivia<'_>| {
+        let span = shift(trivia.span);
+        Trivia {
+            kind: trivia.kind,
+            text: Cow::Borrowed(&source[span.start..span.end]),
+            span,
+        }
+    };
+
+    let span = shift(token.span);
+    let mut rebound = Token::new(
+        token.ttype.clone(),
+        Cow::Borrowed(&source[span.start..span.end]),
+        token.literal.clone(),
+        span,
+    );
+    rebound.leading_trivia = token.leading_trivia.iter().map(trivia).collect();
+    rebound.trailing_trivia = token.trailing_trivia.iter().map(trivia).collect();
+    rebound
+}
@@ -0,0 +1,98 @@
+use std::fs::read_to_string;
+use std::io;
+use std::path::Path;
+
+use crate::span::*;
+
+/// Identifies a file registered in a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(pub usize);
+
+pub struct SourceFile {
+    name: String,
+    source: String,
+    // Byte offset where each line starts.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(name: String, source: String) -> SourceFile {
+        let source = normalize(source);
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        SourceFile {
+            name,
+            source,
+            line_starts,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The 1-based line and column (in characters) of a byte offset.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let start = self.line_starts[line];
+        let end = offset.min(self.source.len());
+        let column = self.source[start..end].chars().count() + 1;
+        (line + 1, column)
+    }
+}
+
+/// Every source file of a program, so spans can be traced back to a file
+/// name and position.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap { files: Vec::new() }
+    }
+
+    /// Registers `source` under `name`. The text is normalised first: a
+    /// byte order mark is dropped, CRLF line endings become LF and a
+    /// leading `#!` line is blanked out (keeping line numbers intact).
+    pub fn add(&mut self, name: String, source: String) -> FileId {
+        self.files.push(SourceFile::new(name, source));
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn load(&mut self, path: &Path) -> io::Result<FileId> {
+        let source = read_to_string(path)?;
+        Ok(self.add(path.display().to_string(), source))
+    }
+
+    pub fn get(&self, file: FileId) -> &SourceFile {
+        &self.files[file.0]
+    }
+
+    /// `file:line:column` of the start of a span.
+    pub fn location(&self, span: &Span) -> String {
+        let file = self.get(span.file);
+        let (line, column) = file.line_col(span.start);
+        format!("{}:{line}:{column}", file.name)
+    }
+}
+
+fn normalize(source: String) -> String {
+    let source = source.strip_prefix('\u{feff}').unwrap_or(&source);
+    let mut source = source.replace("\r\n", "\n");
+    if source.starts_with("#!") {
+        let end = source.find('\n').unwrap_or(source.len());
+        source.replace_range(..end, "");
+    }
+    source
+}
@@ -0,0 +1,26 @@
+use std::fmt::{self};
+
+use crate::source_map::*;
+
+/// Location of a lexeme in the source text.
+///
+/// `start`/`end` are byte offsets (end is exclusive). Lines and columns are
+/// 1-based, columns count characters, and `end_line`/`end_col` point just
+/// past the last character of the lexeme. `file` is the `SourceMap` entry
+/// the offsets refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub file: FileId,
+    pub start: usize,
+    pub end: usize,
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.start_line, self.start_col)
+    }
+}
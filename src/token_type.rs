@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub enum TokenType {
     // Single-character tokens.
     LeftParen,
@@ -10,9 +10,11 @@ pub enum TokenType {
     LeftBracket,
     RightBracket,
     Comma,
+    Colon,
     Dot,
     Minus,
     Plus,
+    Question,
     Semicolon,
     Slash,
     Star,
@@ -30,6 +32,7 @@ pub enum TokenType {
     // Literals.
     Identifier,
     String,
+    Interpolation,
     Number,
 
     // Keywords.
@@ -54,6 +57,7 @@ pub enum TokenType {
 
     // Other.
     Eof,
+    Error,
     // UnterminatedString,
     Unknown,
 }
@@ -71,9 +75,11 @@ impl Display for TokenType {
                 TokenType::LeftBracket => "'['",
                 TokenType::RightBracket => "']'",
                 TokenType::Comma => "','",
+                TokenType::Colon => "':'",
                 TokenType::Dot => "'.'",
                 TokenType::Minus => "'-'",
                 TokenType::Plus => "'+'",
+                TokenType::Question => "'?'",
                 TokenType::Semicolon => "';'",
                 TokenType::Slash => "'/'",
                 TokenType::Star => "'*'",
@@ -87,6 +93,7 @@ impl Display for TokenType {
                 TokenType::LessEqual => "'<='",
                 TokenType::Identifier => "identifier",
                 TokenType::String => "string",
+                TokenType::Interpolation => "string interpolation",
                 TokenType::Number => "number",
                 TokenType::And => "'and'",
                 TokenType::Class => "'class'",
@@ -107,6 +114,7 @@ impl Display for TokenType {
                 TokenType::While => "'while'",
                 TokenType::Import => "'import'",
                 TokenType::Eof => "<EOF>",
+                TokenType::Error => "<Error>",
                 TokenType::Unknown => "<Unknown>",
             }
         )
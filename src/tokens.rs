@@ -1,42 +1,95 @@
+use std::borrow::Cow;
 use std::fmt::{self};
 
 use crate::token_type::*;
 use crate::object::*;
+use crate::span::*;
+use crate::trivia::*;
 
-
+/// A lexeme borrowed from the scanned source. Tokens scanned from an
+/// `io::Read` can't borrow and own their text instead.
 #[derive(Debug, Clone)]
-pub struct Token {
+pub struct Token<'src> {
     pub ttype: TokenType,
-    pub lexeme: String,
+    pub lexeme: Cow<'src, str>,
     pub literal: Option<Object>,
     pub line: usize,
+    pub span: Span,
+    pub leading_trivia: Vec<Trivia<'src>>,
+    pub trailing_trivia: Vec<Trivia<'src>>,
 }
 
-impl Token {
-    pub fn new(ttype: TokenType, lexeme: String, literal: Option<Object>, line: usize) -> Token {
+impl<'src> Token<'src> {
+    pub fn new(
+        ttype: TokenType,
+        lexeme: Cow<'src, str>,
+        literal: Option<Object>,
+        span: Span,
+    ) -> Token<'src> {
         Token {
             ttype,
             lexeme,
             literal,
-            line,
+            line: span.start_line,
+            span,
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
         }
     }
 
-    pub fn eof(line: usize) -> Token {
+    pub fn eof(span: Span) -> Token<'src> {
         Token {
             ttype: TokenType::Eof,
-            lexeme: "".to_string(),
+            lexeme: Cow::Borrowed(""),
             literal: None,
-            line,
+            line: span.start_line,
+            span,
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
         }
     }
 
     pub fn is(&self, ttype: &TokenType) -> bool {
         self.ttype == *ttype
     }
+
+    /// Detaches the token from the source it was scanned from.
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            ttype: self.ttype,
+            lexeme: Cow::Owned(self.lexeme.into_owned()),
+            literal: self.literal,
+            line: self.line,
+            span: self.span,
+            leading_trivia: self
+                .leading_trivia
+                .into_iter()
+                .map(Trivia::into_owned)
+                .collect(),
+            trailing_trivia: self
+                .trailing_trivia
+                .into_iter()
+                .map(Trivia::into_owned)
+                .collect(),
+        }
+    }
+
+    /// The lexeme surrounded by its trivia. In lossless mode, concatenating
+    /// this for every token reproduces the scanned source exactly.
+    pub fn source_text(&self) -> String {
+        let mut text = String::new();
+        for trivia in &self.leading_trivia {
+            text.push_str(&trivia.text);
+        }
+        text.push_str(&self.lexeme);
+        for trivia in &self.trailing_trivia {
+            text.push_str(&trivia.text);
+        }
+        text
+    }
 }
 
-impl fmt::Display for Token {
+impl fmt::Display for Token<'_> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(
             fmt,
@@ -0,0 +1,144 @@
+//! Runs every `.lox` file under `tests/lox` through the `lox-ast` binary and
+//! checks its output against the expectations written in the file:
+//!
+//! - `// expect: <line>` is the next line the program should print,
+//! - `// expect error: <error>` is an error reported on the comment's line,
+//! - `// [line N] <error>` is an error reported on line `N`,
+//! - `// flags: <flags>` are command-line flags to run the file with, e.g.
+//!   `// flags: --strict` for the book's dialect.
+//!
+//! Errors are compared without the file name and column, so
+//! `path:4:7: Error at '=': Invalid assignment target.` matches
+//! `// [line 4] Error at '=': Invalid assignment target.`. A file expecting
+//! errors must exit with 65, any other file with 0.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+struct Expectations {
+    flags: Vec<String>,
+    output: Vec<String>,
+    errors: Vec<String>,
+}
+
+fn parse_expectations(source: &str) -> Expectations {
+    let mut flags = Vec::new();
+    let mut output = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let Some((_, comment)) = line.split_once("// ") else {
+            continue;
+        };
+
+        if let Some(expected) = comment.strip_prefix("expect: ") {
+            output.push(expected.to_string());
+        } else if let Some(expected) = comment.strip_prefix("expect error: ") {
+            errors.push(format!("[line {}] {expected}", i + 1));
+        } else if comment.starts_with("[line ") {
+            errors.push(comment.to_string());
+        } else if let Some(expected) = comment.strip_prefix("flags: ") {
+            flags.extend(expected.split_whitespace().map(str::to_string));
+        }
+    }
+
+    Expectations {
+        flags,
+        output,
+        errors,
+    }
+}
+
+// Rewrites `path:line:column: Error ...` as `[line N] Error ...`.
+fn normalize_error(line: &str) -> String {
+    let Some((location, rest)) = line.split_once(": Error") else {
+        return line.to_string();
+    };
+
+    let mut location = location.rsplit(':');
+    let _column = location.next();
+    match location.next() {
+        Some(line_number) => format!("[line {line_number}] Error{rest}"),
+        None => line.to_string(),
+    }
+}
+
+fn run(path: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let expected = parse_expectations(&source);
+
+    let result = Command::new(env!("CARGO_BIN_EXE_lox-ast"))
+        .args(&expected.flags)
+        .arg(path)
+        .output()
+        .map_err(|err| err.to_string())?;
+
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    // The first line is the `AST Printr:` banner.
+    let output: Vec<String> = stdout.lines().skip(1).map(str::to_string).collect();
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    let errors: Vec<String> = stderr.lines().map(normalize_error).collect();
+
+    let mut failures = Vec::new();
+    if expected.errors.is_empty() && output != expected.output {
+        failures.push(format!(
+            "expected output {:#?}\n got {:#?}",
+            expected.output, output
+        ));
+    }
+    if errors != expected.errors {
+        failures.push(format!(
+            "expected errors {:#?}\n got {:#?}",
+            expected.errors, errors
+        ));
+    }
+
+    let expected_code = if expected.errors.is_empty() { 0 } else { 65 };
+    if result.status.code() != Some(expected_code) {
+        failures.push(format!(
+            "expected exit code {expected_code}, got {:?}",
+            result.status.code()
+        ));
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Couldn't read test directory") {
+        let path = entry.expect("Couldn't read test directory").path();
+        if path.is_dir() {
+            collect(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "lox") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn conformance() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox");
+    let mut files = Vec::new();
+    collect(&root, &mut files);
+    files.sort();
+
+    let mut failed = 0;
+    for path in &files {
+        let name = path.strip_prefix(&root).unwrap_or(path).display();
+        match run(path) {
+            Ok(()) => println!("PASS {name}"),
+            Err(failure) => {
+                failed += 1;
+                println!("FAIL {name}\n{failure}");
+            }
+        }
+    }
+
+    println!("{} passed, {failed} failed", files.len() - failed);
+    assert_eq!(failed, 0, "{failed} conformance test(s) failed");
+}
@@ -0,0 +1,65 @@
+use lox_ast::dialect::*;
+use lox_ast::scanner::*;
+use lox_ast::trivia::*;
+
+fn round_trip(source: &str) -> String {
+    let mut scanner = Scanner::new(source, Dialect::default()).lossless();
+    let (tokens, _) = scanner.scan_tokens();
+    tokens.iter().map(|t| t.source_text()).collect()
+}
+
+#[test]
+fn round_trips_a_whole_program() {
+    let source = include_str!("../tok.lox");
+    assert_eq!(round_trip(source), source);
+}
+
+#[test]
+fn round_trips_edge_cases() {
+    let sources = [
+        "",
+        " ",
+        "\n\n",
+        "var a = 1;\r\nprint a;\r\n",
+        "print 1; // trailing comment",
+        "print 1; // trailing comment\n",
+        "/* only a comment */",
+        "a /* nested /* block */ comment */ b\n",
+        "print \"unterminated",
+        "print 1; /* unterminated /* comment */",
+        "print \"a ${b} c ${\"d ${e}\"} f\";",
+        "print \"${ {\"k\": 1}[\"k\"] }\";",
+        "print \"open ${interpolation",
+        "print r#\"raw \"${x}\" \"#;\n\"\"\"\n  multi\n  line\n  \"\"\"",
+        "a @ # b\t\t\n",
+        "\u{feff}print \"é\"; // ünï",
+    ];
+
+    for source in sources {
+        assert_eq!(round_trip(source), source, "{source:?}");
+    }
+}
+
+#[test]
+fn trivia_attaches_to_the_nearest_token() {
+    let mut scanner = Scanner::new("a // one\n  b /* two */\n", Dialect::default()).lossless();
+    let (tokens, _) = scanner.scan_tokens();
+
+    let kinds = |trivia: &[Trivia<'_>]| trivia.iter().map(|t| t.kind).collect::<Vec<_>>();
+    // Trivia up to the end of a token's line trails it.
+    assert_eq!(
+        kinds(&tokens[0].trailing_trivia),
+        vec![TriviaKind::Whitespace, TriviaKind::LineComment]
+    );
+    // The newline and the indentation lead the next token.
+    assert_eq!(
+        kinds(&tokens[1].leading_trivia),
+        vec![TriviaKind::Newline, TriviaKind::Whitespace]
+    );
+    assert_eq!(
+        kinds(&tokens[1].trailing_trivia),
+        vec![TriviaKind::Whitespace, TriviaKind::BlockComment]
+    );
+    // The final newline leads EOF.
+    assert_eq!(kinds(&tokens[2].leading_trivia), vec![TriviaKind::Newline]);
+}
@@ -0,0 +1,296 @@
+use std::borrow::Cow;
+
+use lox_ast::object::*;
+use lox_ast::span::*;
+use lox_ast::token_type::*;
+use lox_ast::tokens::*;
+
+fn bracket() -> Token<'static> {
+    Token::new(
+        TokenType::RightBracket,
+        Cow::Borrowed("]"),
+        None,
+        Span::default(),
+    )
+}
+
+fn list(elements: &[f64]) -> Object {
+    Object::list(elements.iter().map(|&n| Object::Num(n)).collect())
+}
+
+fn get(object: &Object, index: Object) -> Result<String, String> {
+    object
+        .get_index(&index, &bracket())
+        .map(|value| value.to_string())
+        .map_err(|err| err.message().to_string())
+}
+
+fn set(object: &Object, index: Object, value: Object) -> Result<(), String> {
+    object
+        .set_index(&index, value, &bracket())
+        .map_err(|err| err.message().to_string())
+}
+
+#[test]
+fn list_indexes_must_be_whole_numbers_in_range() {
+    let xs = list(&[10.0, 20.0, 30.0]);
+    assert_eq!(get(&xs, Object::Num(0.0)), Ok("10".to_string()));
+    assert_eq!(get(&xs, Object::Num(2.0)), Ok("30".to_string()));
+    assert_eq!(get(&xs, Object::Num(-0.0)), Ok("10".to_string()));
+
+    let error = |message: &str| Err(message.to_string());
+    assert_eq!(
+        get(&xs, Object::Num(-1.0)),
+        error("List index -1 is out of bounds for length 3.")
+    );
+    assert_eq!(
+        get(&xs, Object::Num(3.0)),
+        error("List index 3 is out of bounds for length 3.")
+    );
+    assert_eq!(
+        get(&xs, Object::Num(1e20)),
+        error("List index 100000000000000000000 is out of bounds for length 3.")
+    );
+    assert_eq!(
+        get(&xs, Object::Num(1.5)),
+        error("List index 1.5 is not a whole number.")
+    );
+    assert_eq!(
+        get(&xs, Object::Num(f64::NAN)),
+        error("List index NaN is not a whole number.")
+    );
+    assert_eq!(
+        get(&xs, Object::Num(f64::INFINITY)),
+        error("List index inf is not a whole number.")
+    );
+    assert_eq!(
+        get(&xs, Object::Str("0".to_string())),
+        error("List index must be a number.")
+    );
+    assert_eq!(get(&xs, Object::Nil), error("List index must be a number."));
+
+    assert_eq!(
+        get(&list(&[]), Object::Num(0.0)),
+        error("List index 0 is out of bounds for length 0.")
+    );
+}
+
+#[test]
+fn writes_are_checked_like_reads() {
+    let xs = list(&[1.0, 2.0]);
+    assert_eq!(set(&xs, Object::Num(1.0), Object::Nil), Ok(()));
+    assert_eq!(xs.to_string(), "[1, nil]");
+
+    for (index, message) in [
+        (
+            Object::Num(2.0),
+            "List index 2 is out of bounds for length 2.",
+        ),
+        (
+            Object::Num(-1.0),
+            "List index -1 is out of bounds for length 2.",
+        ),
+        (Object::Num(0.5), "List index 0.5 is not a whole number."),
+        (
+            Object::Num(f64::NAN),
+            "List index NaN is not a whole number.",
+        ),
+        (Object::True, "List index must be a number."),
+    ] {
+        assert_eq!(set(&xs, index, Object::Nil), Err(message.to_string()));
+    }
+    // A failed write leaves the list alone.
+    assert_eq!(xs.to_string(), "[1, nil]");
+}
+
+#[test]
+fn only_lists_and_maps_can_be_indexed() {
+    let message = "Only lists and maps can be indexed.".to_string();
+    for object in [
+        Object::Num(1.0),
+        Object::Str("abc".to_string()),
+        Object::Nil,
+        Object::True,
+        Object::False,
+    ] {
+        assert_eq!(get(&object, Object::Num(0.0)), Err(message.clone()));
+        assert_eq!(
+            set(&object, Object::Num(0.0), Object::Nil),
+            Err(message.clone())
+        );
+    }
+}
+
+#[test]
+fn index_errors_are_reported_at_the_bracket() {
+    let err = list(&[])
+        .get_index(&Object::Num(0.0), &bracket())
+        .unwrap_err();
+    assert_eq!(err.span(), Some(Span::default()));
+}
+
+#[test]
+fn clones_share_the_list() {
+    let xs = list(&[1.0, 2.0, 3.0]);
+    let alias = xs.clone();
+    set(&alias, Object::Num(0.0), Object::Str("one".to_string())).unwrap();
+    assert_eq!(get(&xs, Object::Num(0.0)), Ok("\"one\"".to_string()));
+    assert_eq!(xs.to_string(), "[\"one\", 2, 3]");
+
+    // A list stored inside another is shared too.
+    let outer = Object::list(vec![xs.clone()]);
+    let inner = outer.get_index(&Object::Num(0.0), &bracket()).unwrap();
+    set(&inner, Object::Num(2.0), Object::Nil).unwrap();
+    assert_eq!(alias.to_string(), "[\"one\", 2, nil]");
+    assert_eq!(outer.to_string(), "[[\"one\", 2, nil]]");
+
+    // Separately built lists are not.
+    let other = list(&[1.0, 2.0, 3.0]);
+    set(&other, Object::Num(0.0), Object::Nil).unwrap();
+    assert_eq!(xs.to_string(), "[\"one\", 2, nil]");
+}
+
+#[test]
+fn lists_that_contain_themselves_print() {
+    let xs = list(&[1.0, 2.0]);
+    set(&xs, Object::Num(0.0), xs.clone()).unwrap();
+    assert_eq!(xs.to_string(), "[[...], 2]");
+    assert_eq!(format!("{xs:?}"), "List([[...], 2])");
+
+    // Only lists on the way down count as a repeat, not every list seen.
+    let shared = list(&[1.0]);
+    let twice = Object::list(vec![shared.clone(), shared.clone()]);
+    assert_eq!(twice.to_string(), "[[1], [1]]");
+
+    // A longer cycle is cut where it comes back around.
+    let ys = list(&[3.0]);
+    set(&xs, Object::Num(1.0), ys.clone()).unwrap();
+    set(&ys, Object::Num(0.0), xs.clone()).unwrap();
+    assert_eq!(xs.to_string(), "[[...], [[...]]]");
+    assert_eq!(ys.to_string(), "[[[...], [...]]]");
+}
+
+fn key(object: Object) -> MapKey {
+    MapKey::new(&object).expect("Object should be a valid key")
+}
+
+#[test]
+fn numbers_compare_by_value_as_keys() {
+    assert_eq!(key(Object::Num(0.0)), key(Object::Num(-0.0)));
+    assert_eq!(key(Object::Num(f64::NAN)), key(Object::Num(-f64::NAN)));
+    assert_eq!(
+        key(Object::Num(f64::NAN)),
+        key(Object::Num(f64::from_bits(0x7ff8_0000_0000_0001)))
+    );
+    assert_ne!(key(Object::Num(1.0)), key(Object::Num(1.0 + f64::EPSILON)));
+
+    // Keys of different types never collide.
+    assert_ne!(key(Object::Num(1.0)), key(Object::Str("1".to_string())));
+    assert_ne!(key(Object::Num(1.0)), key(Object::True));
+    assert_ne!(key(Object::Num(0.0)), key(Object::Nil));
+    assert_ne!(key(Object::False), key(Object::Nil));
+
+    // `-0` reads back as `0`.
+    assert_eq!(key(Object::Num(-0.0)).to_object().to_string(), "0");
+}
+
+#[test]
+fn zero_and_nan_are_one_key_each_in_a_map() {
+    let map = Object::map(LoxMap::new());
+    set(&map, Object::Num(-0.0), Object::Str("zero".to_string())).unwrap();
+    set(&map, Object::Num(f64::NAN), Object::Num(1.0)).unwrap();
+    set(&map, Object::Num(-f64::NAN), Object::Num(2.0)).unwrap();
+
+    assert_eq!(get(&map, Object::Num(0.0)), Ok("\"zero\"".to_string()));
+    assert_eq!(get(&map, Object::Num(f64::NAN)), Ok("2".to_string()));
+    assert_eq!(map.to_string(), "{0: \"zero\", NaN: 2}");
+}
+
+#[test]
+fn lists_and_maps_are_not_keys() {
+    assert!(MapKey::new(&list(&[1.0])).is_none());
+    assert!(MapKey::new(&Object::map(LoxMap::new())).is_none());
+
+    let map = Object::map(LoxMap::new());
+    let message = "Map keys must be strings, numbers, booleans or nil.".to_string();
+    assert_eq!(set(&map, list(&[]), Object::Nil), Err(message.clone()));
+    assert_eq!(get(&map, Object::map(LoxMap::new())), Err(message));
+    assert_eq!(map.to_string(), "{}");
+}
+
+#[test]
+fn missing_keys_are_errors() {
+    let map = Object::map(LoxMap::new());
+    assert_eq!(
+        get(&map, Object::Str("a".to_string())),
+        Err("Undefined key \"a\".".to_string())
+    );
+    assert_eq!(
+        get(&map, Object::Nil),
+        Err("Undefined key nil.".to_string())
+    );
+}
+
+#[test]
+fn maps_keep_insertion_order() {
+    let mut entries = LoxMap::new();
+    assert!(entries.is_empty());
+    entries.insert(key(Object::Str("b".to_string())), Object::Num(1.0));
+    entries.insert(key(Object::Num(2.0)), Object::Num(2.0));
+    entries.insert(key(Object::Str("a".to_string())), Object::Num(3.0));
+    entries.insert(key(Object::Nil), Object::True);
+    // Overwriting a key keeps its original position.
+    entries.insert(key(Object::Num(2.0)), Object::Str("two".to_string()));
+    entries.insert(key(Object::Str("b".to_string())), Object::Num(4.0));
+
+    assert_eq!(entries.len(), 4);
+    let keys: Vec<String> = entries
+        .iter()
+        .map(|(key, _)| key.to_object().to_string())
+        .collect();
+    assert_eq!(keys, vec!["\"b\"", "2", "\"a\"", "nil"]);
+    assert_eq!(
+        entries.get(&key(Object::Num(2.0))).map(Object::to_string),
+        Some("\"two\"".to_string())
+    );
+
+    let map = Object::map(entries);
+    assert_eq!(
+        map.to_string(),
+        "{\"b\": 4, 2: \"two\", \"a\": 3, nil: true}"
+    );
+
+    // Writes through the object go to the end, unless the key exists.
+    set(&map, Object::False, Object::Nil).unwrap();
+    set(&map, Object::Str("a".to_string()), Object::Num(5.0)).unwrap();
+    assert_eq!(
+        map.to_string(),
+        "{\"b\": 4, 2: \"two\", \"a\": 5, nil: true, false: nil}"
+    );
+}
+
+#[test]
+fn clones_share_the_map() {
+    let map = Object::map(LoxMap::new());
+    let alias = map.clone();
+    set(&alias, Object::Str("k".to_string()), Object::Num(1.0)).unwrap();
+    assert_eq!(map.to_string(), "{\"k\": 1}");
+
+    // Nested collections display recursively.
+    set(&map, Object::Num(1.0), list(&[1.0, 2.0])).unwrap();
+    assert_eq!(alias.to_string(), "{\"k\": 1, 1: [1, 2]}");
+}
+
+#[test]
+fn maps_that_contain_themselves_print() {
+    let map = Object::map(LoxMap::new());
+    set(&map, Object::Str("self".to_string()), map.clone()).unwrap();
+    assert_eq!(map.to_string(), "{\"self\": {...}}");
+    assert_eq!(format!("{map:?}"), "Map({\"self\": {...}})");
+
+    // Cycles through lists are cut at whichever collection repeats.
+    let xs = Object::list(vec![map.clone()]);
+    set(&map, Object::Str("list".to_string()), xs.clone()).unwrap();
+    assert_eq!(map.to_string(), "{\"self\": {...}, \"list\": [{...}]}");
+    assert_eq!(xs.to_string(), "[{\"self\": {...}, \"list\": [...]}]");
+}
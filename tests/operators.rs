@@ -0,0 +1,168 @@
+use std::sync::LazyLock;
+
+use lox_ast::dialect::*;
+use lox_ast::expr::*;
+use lox_ast::operators::*;
+use lox_ast::perser::*;
+use lox_ast::printer::*;
+use lox_ast::scanner::*;
+use lox_ast::stmt::*;
+use lox_ast::token_type::*;
+
+// `!` doubles as a right-associative infix operator between `+` and `*`,
+// and unary `-` binds as loosely as `+`.
+static EXTENDED: LazyLock<OperatorTable> = LazyLock::new(|| {
+    let mut table = OperatorTable::lox();
+    table
+        .register_infix(TokenType::Bang, PREC_TERM + 5, Associativity::Right)
+        .register_prefix(TokenType::Minus, PREC_TERM);
+    table
+});
+
+fn parse(source: &str, dialect: Dialect) -> String {
+    parse_with(source, dialect, dialect.operators())
+}
+
+fn parse_with(source: &str, dialect: Dialect, operators: OperatorTable) -> String {
+    let mut scanner = Scanner::new(source, dialect);
+    let (tokens, errors) = scanner.scan_tokens();
+    assert!(errors.is_empty(), "{source}");
+
+    let (statements, errors) = Perser::new(tokens, dialect).operators(operators).parse();
+    assert!(errors.is_empty(), "{source}");
+    statements
+        .iter()
+        .map(|stmt| AstPrinter.print_stmt(stmt).unwrap())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn dialects_parse_with_their_own_operators() {
+    let dialect = Dialect::extended().with_operators(&EXTENDED);
+    assert_eq!(
+        parse("a + b ! c ! d * e;", dialect),
+        "(; (+ a (! b (! c (* d e)))))"
+    );
+    assert_eq!(parse("a * b ! c - d;", dialect), "(; (- (! (* a b) c) d))");
+    // Prefix `!` is untouched by registering it as an infix operator.
+    assert_eq!(parse("!a ! !b;", dialect), "(; (! (! a) (! b)))");
+
+    // The book's dialects are unchanged.
+    assert_eq!(
+        parse("-a * b - c;", Dialect::extended()),
+        "(; (- (* (- a) b) c))"
+    );
+    assert_eq!(
+        parse("-a * b - c;", Dialect::lox()),
+        "(; (- (* (- a) b) c))"
+    );
+}
+
+#[test]
+fn prefix_precedence_bounds_the_operand() {
+    let dialect = Dialect::extended().with_operators(&EXTENDED);
+    // `-` now takes everything binding at least as tightly as `+`.
+    assert_eq!(parse("-a * b;", dialect), "(; (- (* a b)))");
+    assert_eq!(parse("-a + b;", dialect), "(; (- (+ a b)))");
+    assert_eq!(parse("-a < b;", dialect), "(; (< (- a) b))");
+    assert_eq!(parse("- -a;", dialect), "(; (- (- a)))");
+}
+
+#[test]
+fn associativity() {
+    let mut table = OperatorTable::lox();
+    table.register_infix(TokenType::Minus, PREC_TERM, Associativity::Right);
+    assert_eq!(
+        parse_with("a - b - c + d;", Dialect::extended(), table),
+        "(; (- a (- b (+ c d))))"
+    );
+
+    assert_eq!(
+        parse("a - b - c + d;", Dialect::extended()),
+        "(; (+ (- (- a b) c) d))"
+    );
+}
+
+#[test]
+fn registering_a_token_again_replaces_it() {
+    // `+` binds tighter than `*`, and `and` looser than `or`.
+    let mut table = OperatorTable::lox();
+    table
+        .register_infix(TokenType::Plus, PREC_FACTOR + 5, Associativity::Left)
+        .register_logical(TokenType::And, PREC_OR - 5);
+    assert_eq!(
+        parse_with("a * b + c;", Dialect::extended(), table.clone()),
+        "(; (* a (+ b c)))"
+    );
+    assert_eq!(
+        parse_with("a or b and c or d;", Dialect::extended(), table),
+        "(; (and (or a b) (or c d)))"
+    );
+}
+
+#[test]
+fn logical_operators_build_logical_nodes() {
+    let mut table = OperatorTable::new();
+    table
+        .register_logical(TokenType::Or, PREC_OR)
+        .register_infix(TokenType::EqualEqual, PREC_EQUALITY, Associativity::Left)
+        .register_logical(TokenType::Star, PREC_FACTOR);
+
+    let mut scanner = Scanner::new("a or b == c * d;", Dialect::extended());
+    let (tokens, _) = scanner.scan_tokens();
+    let (statements, errors) = Perser::new(tokens, Dialect::extended())
+        .operators(table)
+        .parse();
+    assert!(errors.is_empty());
+
+    let Stmt::Expression(ExpressionStmt { expression }) = &statements[0] else {
+        panic!("Expected an expression statement");
+    };
+    let Expr::Logical(or) = expression.as_ref() else {
+        panic!("`or` should be logical");
+    };
+    let Expr::Binary(equals) = or.right.as_ref() else {
+        panic!("`==` should be binary");
+    };
+    assert!(matches!(equals.right.as_ref(), Expr::Logical(_)));
+}
+
+#[test]
+fn unregistered_operators_end_the_expression() {
+    let table = OperatorTable::new();
+    assert!(table.infix(&TokenType::Plus).is_none());
+    assert!(table.prefix(&TokenType::Minus).is_none());
+
+    let mut scanner = Scanner::new("a + b;", Dialect::extended());
+    let (tokens, _) = scanner.scan_tokens();
+    let (_, errors) = Perser::new(tokens, Dialect::extended())
+        .operators(table)
+        .parse();
+    assert_eq!(errors[0].message(), "Expect ';' after expression.");
+}
+
+#[test]
+fn lookups_report_what_was_registered() {
+    let table = OperatorTable::lox();
+    let minus = table.infix(&TokenType::Minus).unwrap();
+    assert_eq!(minus.precedence, PREC_TERM);
+    assert_eq!(minus.associativity, Associativity::Left);
+    assert_eq!(minus.kind, InfixKind::Binary);
+    assert_eq!(table.prefix(&TokenType::Minus), Some(PREC_UNARY));
+    assert_eq!(
+        table.infix(&TokenType::And).unwrap().kind,
+        InfixKind::Logical
+    );
+
+    let ladder = [
+        PREC_OR,
+        PREC_AND,
+        PREC_EQUALITY,
+        PREC_COMPARISON,
+        PREC_TERM,
+        PREC_FACTOR,
+        PREC_UNARY,
+    ];
+    assert!(ladder.windows(2).all(|pair| pair[0] < pair[1]));
+}
@@ -0,0 +1,126 @@
+use std::io::{self, Read};
+
+use lox_ast::dialect::*;
+use lox_ast::error::*;
+use lox_ast::scanner::*;
+use lox_ast::tokens::*;
+
+// Hands out at most `step` bytes per read, optionally failing once `fail_at`
+// bytes have been read.
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    step: usize,
+    fail_at: Option<usize>,
+    read: usize,
+    interrupted: bool,
+}
+
+impl<'a> Trickle<'a> {
+    fn new(source: &'a str, step: usize) -> Trickle<'a> {
+        Trickle {
+            bytes: source.as_bytes(),
+            step,
+            fail_at: None,
+            read: 0,
+            interrupted: false,
+        }
+    }
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.fail_at == Some(self.read) {
+            return Err(io::Error::other("disk on fire"));
+        }
+        // Every other read is interrupted, which the scanner must retry.
+        self.interrupted = !self.interrupted;
+        if self.interrupted {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+
+        let mut n = self.step.min(buf.len()).min(self.bytes.len());
+        if let Some(fail_at) = self.fail_at {
+            n = n.min(fail_at - self.read);
+        }
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        self.read += n;
+        Ok(n)
+    }
+}
+
+// Everything observable about a token or error, as a comparable string.
+fn describe(items: Vec<Result<Token<'_>, LoxError>>) -> Vec<String> {
+    items
+        .into_iter()
+        .map(|item| match item {
+            Ok(t) => format!("{:?} {:?} {:?} {:?}", t.ttype, t.lexeme, t.literal, t.span),
+            Err(e) => format!("error {:?} {:?}", e.message(), e.span()),
+        })
+        .collect()
+}
+
+fn from_str(source: &str) -> Vec<String> {
+    describe(Scanner::new(source, Dialect::default()).collect())
+}
+
+fn from_reader<R: Read>(reader: R) -> Vec<String> {
+    describe(Scanner::from_reader(reader, Dialect::default()).collect())
+}
+
+const PROGRAM: &str = "var café = \"naïve ${1 + 2} 😀\";\n\
+                       // 注释 with ünï\n\
+                       print café /* ✓ */ >= 0x1F;\n\
+                       \"unterminated";
+
+#[test]
+fn one_byte_reads_match_str_input() {
+    let expected = from_str(PROGRAM);
+    assert_eq!(from_reader(Trickle::new(PROGRAM, 1)), expected);
+    assert_eq!(from_reader(Trickle::new(PROGRAM, 3)), expected);
+    assert_eq!(from_reader(PROGRAM.as_bytes()), expected);
+}
+
+#[test]
+fn lexemes_can_straddle_chunk_boundaries() {
+    // Reads come in 8 KiB chunks. Place a string, an identifier and a
+    // multi-byte character across the first boundary, and a comment across
+    // the second.
+    let mut source = " ".repeat(8 * 1024 - 10);
+    source.push_str("\"a string spanning chunks\" identifier_é ");
+    while source.len() < 8 * 1024 - 1 {
+        source.push(' ');
+    }
+    source.push('é');
+    while source.len() < 16 * 1024 - 5 {
+        source.push(' ');
+    }
+    source.push_str("/* comment */ last");
+
+    let expected = from_str(&source);
+    assert_eq!(from_reader(source.as_bytes()), expected);
+    assert_eq!(from_reader(Trickle::new(&source, 1)), expected);
+    assert!(expected[0].contains("a string spanning chunks"));
+}
+
+#[test]
+fn read_errors_are_reported_where_the_input_stops() {
+    let mut reader = Trickle::new("print 12345;", 4);
+    reader.fail_at = Some(8);
+
+    let items: Vec<_> = Scanner::from_reader(reader, Dialect::default()).collect();
+    let errors: Vec<_> = items.iter().filter_map(|i| i.as_ref().err()).collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message(), "Unable to read source: disk on fire");
+    assert_eq!(errors[0].span().unwrap().start, 8);
+
+    // The tokens read before the failure are still produced, and the
+    // scan ends with EOF.
+    let lexemes: Vec<_> = items
+        .iter()
+        .filter_map(|i| i.as_ref().ok())
+        .map(|t| t.lexeme.to_string())
+        .collect();
+    assert_eq!(lexemes.first().map(String::as_str), Some("print"));
+    assert_eq!(lexemes.last().map(String::as_str), Some(""));
+}
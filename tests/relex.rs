@@ -0,0 +1,223 @@
+use std::ops::Range;
+
+use lox_ast::dialect::*;
+use lox_ast::error::*;
+use lox_ast::scanner::*;
+use lox_ast::tokens::*;
+
+const PROGRAM: &str = "var a = 1.5;\n\
+                       print \"x ${a + {\"k\": 2}[\"k\"]} y ${ \"in${a}ner\" } z\";\n\
+                       /* block /* nested */ */ fun f(b) { return b; } // done\n\
+                       print r\"raw\" + \"\"\"\n  multi\n  \"\"\";\n";
+
+// Everything about a token that relexing must get right.
+fn describe(token: &Token<'_>) -> String {
+    let trivia = |trivia: &[lox_ast::trivia::Trivia<'_>]| {
+        trivia
+            .iter()
+            .map(|t| format!("{:?} {:?} {:?}", t.kind, t.text, t.span))
+            .collect::<Vec<_>>()
+    };
+    format!(
+        "{:?} {:?} {:?} {:?} {:?} {:?}",
+        token.ttype,
+        token.lexeme,
+        token.literal,
+        token.span,
+        trivia(&token.leading_trivia),
+        trivia(&token.trailing_trivia)
+    )
+}
+
+fn scanner(source: &str, lossless: bool) -> Scanner<'_> {
+    let scanner = Scanner::new(source, Dialect::default());
+    if lossless {
+        scanner.lossless()
+    } else {
+        scanner
+    }
+}
+
+fn describe_error(err: &LoxError) -> String {
+    format!("{:?} {:?}", err.message(), err.span())
+}
+
+// The tokens and errors of a full scan.
+fn scan(source: &str, lossless: bool) -> (Vec<Token<'_>>, Vec<LoxError>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    for item in scanner(source, lossless) {
+        match item {
+            Ok(token) => tokens.push(token),
+            Err(err) => errors.push(err),
+        }
+    }
+    (tokens, errors)
+}
+
+fn relex(source: &str, edit: &Edit, lossless: bool) -> (Vec<String>, Vec<String>) {
+    let mut edited = source.to_string();
+    edited.replace_range(edit.range.clone(), &edit.text);
+
+    let (old, old_errors) = scan(source, lossless);
+    let (tokens, errors) = scanner(&edited, lossless).relex(&old, &old_errors, edit);
+    (
+        tokens.iter().map(describe).collect(),
+        errors.iter().map(describe_error).collect(),
+    )
+}
+
+// Applies the edit both ways and checks relexing matches a full rescan,
+// errors included.
+fn check(source: &str, range: Range<usize>, text: &str) {
+    let mut edited = source.to_string();
+    edited.replace_range(range.clone(), text);
+    let edit = Edit {
+        range,
+        text: text.to_string(),
+    };
+
+    for lossless in [false, true] {
+        let (tokens, errors) = scan(&edited, lossless);
+        let expected = (
+            tokens.iter().map(describe).collect::<Vec<_>>(),
+            errors.iter().map(describe_error).collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            relex(source, &edit, lossless),
+            expected,
+            "{edit:?} on {source:?} (lossless: {lossless})"
+        );
+    }
+}
+
+fn at(needle: &str) -> usize {
+    PROGRAM.find(needle).expect("Needle isn't in the program")
+}
+
+#[test]
+fn edits_inside_interpolations() {
+    let inner = at("a + {");
+    check(PROGRAM, inner..inner + 1, "bb");
+    check(PROGRAM, inner..inner, "(");
+    // Closing the interpolation early, and opening another one.
+    check(PROGRAM, inner + 1..inner + 1, "}");
+    check(PROGRAM, inner..inner, "${");
+    let nested = at("in${a}");
+    check(PROGRAM, nested + 4..nested + 5, "b + c");
+    check(PROGRAM, nested + 2..nested + 3, "");
+}
+
+#[test]
+fn inserting_and_deleting_quotes() {
+    let quote = at("\"x ${");
+    check(PROGRAM, quote..quote, "\"");
+    check(PROGRAM, quote..quote + 1, "");
+    check(PROGRAM, 0..0, "\"");
+    let raw = at("\"raw\"");
+    check(PROGRAM, raw + 4..raw + 5, "");
+    check(PROGRAM, raw..raw, "\"");
+}
+
+#[test]
+fn inserting_and_deleting_block_comments() {
+    let block = at("/* block");
+    check(PROGRAM, block..block + 2, "");
+    check(PROGRAM, 0..0, "/*");
+    let fun = at("fun");
+    check(PROGRAM, fun..fun, "/* ");
+    let close = at("*/ */");
+    check(PROGRAM, close..close + 2, "");
+}
+
+#[test]
+fn inserting_and_deleting_newlines() {
+    let newline = at("\nprint \"x");
+    check(PROGRAM, newline..newline + 1, "");
+    check(PROGRAM, newline..newline + 1, "\n\n\n");
+    let equals = at("= 1.5");
+    check(PROGRAM, equals..equals, "\n");
+    let multi = at("  multi");
+    check(PROGRAM, multi - 1..multi, "");
+    check(PROGRAM, multi..multi, "\n");
+}
+
+#[test]
+fn edits_at_the_start_and_end() {
+    check(PROGRAM, 0..0, "x");
+    check(PROGRAM, 0..3, "let");
+    check(PROGRAM, 0..4, "");
+    let end = PROGRAM.len();
+    check(PROGRAM, end..end, "print 2;");
+    check(PROGRAM, end - 1..end, "");
+    check(PROGRAM, end..end, "// trailing");
+    check(PROGRAM, 0..end, "");
+    check("", 0..0, "var a;");
+}
+
+#[test]
+fn edits_that_change_the_preceding_token() {
+    // `1.5` becomes `1.55`, and `1.` only becomes a number once the digit
+    // after it arrives.
+    let number = at("1.5");
+    check(PROGRAM, number + 3..number + 3, "5");
+    check(PROGRAM, number + 2..number + 3, "");
+    check("print 1.;", 8..8, "5");
+    check("print a", 7..7, "b");
+}
+
+// Without lossless mode the first token starts after the comments and
+// whitespace in front of it, so edits there come before every token.
+#[test]
+fn edits_before_the_first_token() {
+    check("// header\nvar x = 1;", 4..5, "");
+    check("// header\nvar x = 1;", 0..1, "");
+    check("\n//", 0..0, ";${");
+    check("  \n\tprint 1;", 1..1, "x");
+    check("/* a */ b", 3..4, "*/ c /*");
+    every_position(LEADING_TRIVIA);
+}
+
+const LEADING_TRIVIA: &str = "  // header\n/* note */\n\tvar x = \"${y}\";\n";
+
+// Inserting each text before every character, and replacing every
+// character with it.
+fn every_position(source: &str) {
+    let mut boundaries: Vec<usize> = source.char_indices().map(|(i, _)| i).collect();
+    boundaries.push(source.len());
+
+    for text in ["", "\"", "}", "${", "\n", "/*", "é", "1"] {
+        for pair in boundaries.windows(2) {
+            check(source, pair[0]..pair[0], text);
+            check(source, pair[0]..pair[1], text);
+        }
+    }
+}
+
+#[test]
+fn edits_at_every_position() {
+    every_position(PROGRAM);
+}
+
+#[test]
+fn errors_outside_the_edit_survive() {
+    let source = "var a = @;\nprint \"\\q\";\nvar b = 1;\nprint #;\n/* open";
+    let edit = Edit {
+        range: 31..32,
+        text: "22\n".to_string(),
+    };
+    for lossless in [false, true] {
+        let (_, errors) = relex(source, &edit, lossless);
+        assert_eq!(errors.len(), 4, "{errors:#?}");
+        // The errors before the edit are untouched, the ones after it move
+        // down a line.
+        assert!(errors[0].starts_with("\"Unexpected character\""));
+        assert!(errors[1].contains("start_line: 2"));
+        assert!(errors[2].contains("start: 42") && errors[2].contains("start_line: 5"));
+        assert!(errors[3].starts_with("\"Unterminated block comment\""));
+    }
+    check(source, 31..32, "22\n");
+    check(source, 0..0, "  ");
+    // Fixing an error drops it.
+    check(source, 8..9, "1");
+}
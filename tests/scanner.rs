@@ -0,0 +1,412 @@
+use lox_ast::dialect::*;
+use lox_ast::error::*;
+use lox_ast::object::*;
+use lox_ast::scanner::*;
+use lox_ast::token_type::*;
+use lox_ast::tokens::*;
+
+fn scan(source: &str) -> (Vec<Token<'_>>, Vec<LoxError>) {
+    let mut scanner = Scanner::new(source, Dialect::default());
+    let (tokens, errors) = scanner.scan_tokens();
+    (tokens.clone(), errors.clone())
+}
+
+// `(lexeme, start, end, start_line, start_col, end_line, end_col)`
+fn spans<'a>(tokens: &'a [Token<'_>]) -> Vec<(&'a str, usize, usize, usize, usize, usize, usize)> {
+    tokens
+        .iter()
+        .map(|t| {
+            let s = t.span;
+            (
+                t.lexeme.as_ref(),
+                s.start,
+                s.end,
+                s.start_line,
+                s.start_col,
+                s.end_line,
+                s.end_col,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn spans_cover_each_lexeme() {
+    let (tokens, errors) = scan("var x = 1;\n  print x >= 10;");
+    assert!(errors.is_empty());
+    assert_eq!(
+        spans(&tokens),
+        vec![
+            ("var", 0, 3, 1, 1, 1, 4),
+            ("x", 4, 5, 1, 5, 1, 6),
+            ("=", 6, 7, 1, 7, 1, 8),
+            ("1", 8, 9, 1, 9, 1, 10),
+            (";", 9, 10, 1, 10, 1, 11),
+            ("print", 13, 18, 2, 3, 2, 8),
+            ("x", 19, 20, 2, 9, 2, 10),
+            (">=", 21, 23, 2, 11, 2, 13),
+            ("10", 24, 26, 2, 14, 2, 16),
+            (";", 26, 27, 2, 16, 2, 17),
+            ("", 27, 27, 2, 17, 2, 17),
+        ]
+    );
+    assert!(tokens.last().unwrap().is(&TokenType::Eof));
+}
+
+#[test]
+fn multi_line_lexemes_end_on_their_last_line() {
+    let (tokens, _) = scan("\"a\nbc\" x");
+    assert_eq!(
+        spans(&tokens),
+        vec![
+            ("\"a\nbc\"", 0, 6, 1, 1, 2, 4),
+            ("x", 7, 8, 2, 5, 2, 6),
+            ("", 8, 8, 2, 6, 2, 6)
+        ]
+    );
+}
+
+#[test]
+fn columns_count_characters_not_bytes() {
+    let (tokens, _) = scan("\"é\" + x");
+    assert_eq!(
+        spans(&tokens),
+        vec![
+            ("\"é\"", 0, 4, 1, 1, 1, 4),
+            ("+", 5, 6, 1, 5, 1, 6),
+            ("x", 7, 8, 1, 7, 1, 8),
+            ("", 8, 8, 1, 8, 1, 8),
+        ]
+    );
+}
+
+#[test]
+fn eof_span_sits_after_trailing_newlines_and_comments() {
+    let (tokens, _) = scan("x\n// done\n");
+    let eof = tokens.last().unwrap();
+    assert!(eof.is(&TokenType::Eof));
+    assert_eq!(spans(&tokens[1..]), vec![("", 10, 10, 3, 1, 3, 1)]);
+    assert_eq!(eof.line, 3);
+
+    let (tokens, _) = scan("");
+    assert_eq!(spans(&tokens), vec![("", 0, 0, 1, 1, 1, 1)]);
+}
+
+// `(message, start, end)` of each error.
+fn error_spans(errors: &[LoxError]) -> Vec<(&str, usize, usize)> {
+    errors
+        .iter()
+        .map(|e| {
+            let span = e.span().unwrap();
+            (e.message(), span.start, span.end)
+        })
+        .collect()
+}
+
+fn string_value(source: &str) -> String {
+    let (tokens, errors) = scan(source);
+    assert!(errors.is_empty(), "{source}: {:?}", error_spans(&errors));
+    match &tokens[0].literal {
+        Some(Object::Str(s)) => s.clone(),
+        other => panic!("{source}: expected a string, got {other:?}"),
+    }
+}
+
+#[test]
+fn escapes_decode_to_their_characters() {
+    assert_eq!(string_value(r#""a\nb""#), "a\nb");
+    assert_eq!(string_value(r#""\t\r\\\"\0\$""#), "\t\r\\\"\0$");
+    assert_eq!(string_value(r#""\u{41}\u{e9}\u{1F600}""#), "Aé😀");
+    assert_eq!(string_value(r#""\u{10FFFF}""#), "\u{10FFFF}");
+}
+
+#[test]
+fn bad_escapes_are_reported_at_the_backslash() {
+    let (tokens, errors) = scan(r#""a\qb\u{110000}c\u{D800}""#);
+    assert_eq!(
+        error_spans(&errors),
+        vec![
+            ("Unknown escape sequence '\\q'", 2, 4),
+            ("Invalid unicode code point '\\u{110000}'", 5, 15),
+            ("Invalid unicode code point '\\u{D800}'", 16, 24),
+        ]
+    );
+    // The whole literal is still one token, marked as an error.
+    assert!(tokens[0].is(&TokenType::Error));
+    assert!(tokens[1].is(&TokenType::Eof));
+}
+
+#[test]
+fn malformed_unicode_escapes() {
+    let (_, errors) = scan(r#""\u41""#);
+    assert_eq!(error_spans(&errors), vec![("Expect '{' after '\\u'", 1, 3)]);
+
+    let (_, errors) = scan(r#""\u{41""#);
+    assert_eq!(
+        error_spans(&errors),
+        vec![("Expect '}' after unicode escape digits", 1, 6)]
+    );
+
+    let (_, errors) = scan(r#""\u{}" "\u{1234567}""#);
+    assert_eq!(
+        error_spans(&errors),
+        vec![
+            ("Unicode escape must have 1 to 6 hex digits", 1, 5),
+            ("Unicode escape must have 1 to 6 hex digits", 8, 19),
+        ]
+    );
+}
+
+#[test]
+fn every_lexical_error_is_collected() {
+    let (tokens, errors) = scan("var a = @;\nprint a # 1;\n\"open");
+    assert_eq!(
+        error_spans(&errors),
+        vec![
+            ("Unexpected character", 8, 9),
+            ("Unexpected character", 19, 20),
+            ("Unterminated string", 24, 29),
+        ]
+    );
+    assert_eq!(errors[1].span().unwrap().start_line, 2);
+    assert_eq!(errors[2].span().unwrap().start_line, 3);
+
+    // Scanning carries on after each error, leaving an `Error` token in its
+    // place.
+    let ttypes: Vec<TokenType> = tokens.iter().map(|t| t.ttype.clone()).collect();
+    assert_eq!(
+        ttypes,
+        vec![
+            TokenType::Var,
+            TokenType::Identifier,
+            TokenType::Equal,
+            TokenType::Error,
+            TokenType::Semicolon,
+            TokenType::Print,
+            TokenType::Identifier,
+            TokenType::Error,
+            TokenType::Number,
+            TokenType::Semicolon,
+            TokenType::Error,
+            TokenType::Eof,
+        ]
+    );
+}
+
+fn lexemes<'a>(tokens: &'a [Token<'_>]) -> Vec<&'a str> {
+    tokens.iter().map(|t| t.lexeme.as_ref()).collect()
+}
+
+#[test]
+fn block_comments_nest() {
+    let (tokens, errors) = scan("a /* one /* two */ still */ b /**/ c /* * / */ d");
+    assert!(errors.is_empty());
+    assert_eq!(lexemes(&tokens), vec!["a", "b", "c", "d", ""]);
+
+    let (tokens, errors) = scan("a /* line\n/* nested\n*/ */ b");
+    assert!(errors.is_empty());
+    assert_eq!(lexemes(&tokens), vec!["a", "b", ""]);
+    assert_eq!(tokens[1].span.start_line, 3);
+}
+
+#[test]
+fn unterminated_block_comments_report_the_opening() {
+    let (tokens, errors) = scan("a\n  /* open /* inner */ never closed");
+    assert_eq!(
+        error_spans(&errors),
+        vec![("Unterminated block comment", 4, 6)]
+    );
+    let span = errors[0].span().unwrap();
+    assert_eq!((span.start_line, span.start_col), (2, 3));
+    assert_eq!(
+        lexemes(&tokens),
+        vec!["a", "/* open /* inner */ never closed", ""]
+    );
+    assert!(tokens[1].is(&TokenType::Error));
+
+    let (_, errors) = scan("/* a /* b */");
+    assert_eq!(
+        error_spans(&errors),
+        vec![("Unterminated block comment", 0, 2)]
+    );
+}
+
+fn number_value(source: &str) -> f64 {
+    let (tokens, errors) = scan(source);
+    assert!(errors.is_empty(), "{source}: {:?}", error_spans(&errors));
+    assert_eq!(tokens.len(), 2, "{source} is a single token");
+    match tokens[0].literal {
+        Some(Object::Num(n)) => n,
+        ref other => panic!("{source}: expected a number, got {other:?}"),
+    }
+}
+
+#[test]
+fn number_literals() {
+    assert_eq!(number_value("42"), 42.0);
+    assert_eq!(number_value("1.5"), 1.5);
+    assert_eq!(number_value("0xff"), 255.0);
+    assert_eq!(number_value("0XFF"), 255.0);
+    assert_eq!(number_value("0o17"), 15.0);
+    assert_eq!(number_value("0b1010"), 10.0);
+    assert_eq!(number_value("1_000_000"), 1_000_000.0);
+    assert_eq!(number_value("0xdead_beef"), 3_735_928_559.0);
+    assert_eq!(number_value("1.5e3"), 1500.0);
+    assert_eq!(number_value("2E-2"), 0.02);
+    assert_eq!(number_value("1e+2"), 100.0);
+    assert_eq!(number_value("1_0.2_5"), 10.25);
+}
+
+#[test]
+fn malformed_number_literals() {
+    let cases = [
+        ("0x", "Expect hexadecimal digits after '0x'", 0, 2),
+        ("0o", "Expect octal digits after '0o'", 0, 2),
+        ("0b102", "Invalid digit '2' in binary literal", 0, 5),
+        ("0xfg", "Invalid digit 'g' in hexadecimal literal", 0, 4),
+        ("1e", "Expect digits in number exponent", 0, 2),
+        ("1e+", "Expect digits in number exponent", 0, 3),
+        ("1__0", "Digit separator '_' must be between digits", 0, 2),
+        ("1_", "Digit separator '_' must be between digits", 0, 2),
+        ("0x_1", "Expect hexadecimal digits after '0x'", 0, 2),
+        (
+            "0x1_0000_0000_0000_0000_0000_0000_0000_0000",
+            "Number literal is too large",
+            0,
+            43,
+        ),
+    ];
+
+    for (source, message, start, end) in cases {
+        let (tokens, errors) = scan(source);
+        assert_eq!(
+            error_spans(&errors),
+            vec![(message, start, end)],
+            "{source}"
+        );
+        // The rest of the literal is swallowed into a single error token.
+        assert_eq!(lexemes(&tokens), vec![source, ""], "{source}");
+        assert!(tokens[0].is(&TokenType::Error), "{source}");
+    }
+}
+
+#[test]
+fn identifiers_follow_unicode_xid() {
+    let (tokens, errors) = scan("foo_bar _private x1 变量 ünï cafe\u{301} __");
+    assert!(errors.is_empty());
+    assert_eq!(
+        lexemes(&tokens),
+        vec![
+            "foo_bar",
+            "_private",
+            "x1",
+            "变量",
+            "ünï",
+            "cafe\u{301}",
+            "__",
+            ""
+        ]
+    );
+    assert!(tokens[..7].iter().all(|t| t.is(&TokenType::Identifier)));
+    // Columns count characters, so the multi-byte identifiers don't skew
+    // the ones after them.
+    assert_eq!((tokens[4].span.start_col, tokens[4].span.end_col), (24, 27));
+}
+
+// `_` used to end an identifier unless it was also the first character, so
+// `foo_bar` scanned as `foo`, `_bar`.
+#[test]
+fn underscore_continues_an_identifier() {
+    let (tokens, _) = scan("foo_bar");
+    assert_eq!(lexemes(&tokens), vec!["foo_bar", ""]);
+
+    let (tokens, _) = scan("var_ = for_each;");
+    assert_eq!(lexemes(&tokens), vec!["var_", "=", "for_each", ";", ""]);
+    assert!(tokens[0].is(&TokenType::Identifier));
+    assert!(tokens[2].is(&TokenType::Identifier));
+}
+
+#[test]
+fn non_identifier_characters_are_unexpected() {
+    let (tokens, errors) = scan("a😀b");
+    assert_eq!(error_spans(&errors), vec![("Unexpected character", 1, 5)]);
+    assert_eq!(lexemes(&tokens), vec!["a", "😀", "b", ""]);
+}
+
+#[test]
+fn raw_strings_take_every_character_literally() {
+    assert_eq!(string_value(r#"r"C:\path\n""#), r"C:\path\n");
+    assert_eq!(string_value(r#"r"${x}""#), "${x}");
+    assert_eq!(string_value("r\"two\nlines\""), "two\nlines");
+    assert_eq!(string_value(r##"r#"say "hi""#"##), r#"say "hi""#);
+    assert_eq!(string_value(r###"r##"a "# b"##"###), r##"a "# b"##);
+    assert_eq!(string_value(r#"r"""#), "");
+
+    // Only an `r` right before the quote or fence starts a raw string.
+    let (tokens, _) = scan("r + raw");
+    assert_eq!(lexemes(&tokens), vec!["r", "+", "raw", ""]);
+    assert!(tokens[0].is(&TokenType::Identifier));
+}
+
+#[test]
+fn malformed_raw_strings() {
+    let (tokens, errors) = scan("r#x");
+    assert_eq!(
+        error_spans(&errors),
+        vec![("Expect '\"' to start raw string", 0, 2)]
+    );
+    assert_eq!(lexemes(&tokens), vec!["r#", "x", ""]);
+
+    let (_, errors) = scan(r#"r"abc"#);
+    assert_eq!(
+        error_spans(&errors),
+        vec![("Unterminated raw string", 0, 5)]
+    );
+
+    // The closing quote needs as many '#'s as the opening one.
+    let (_, errors) = scan(r##"r#"abc" x"##);
+    assert_eq!(
+        error_spans(&errors),
+        vec![("Unterminated raw string", 0, 9)]
+    );
+}
+
+#[test]
+fn multi_line_strings_strip_common_indentation() {
+    let source = "\"\"\"\n    select *\n      from users\n    \"\"\"";
+    assert_eq!(string_value(source), "select *\n  from users");
+
+    // The closing line counts towards the common indentation.
+    assert_eq!(string_value("\"\"\"\n    a\n  \"\"\""), "  a");
+
+    // Text right after the opening quotes keeps the first line, which has
+    // no indentation of its own.
+    assert_eq!(string_value("\"\"\"a\n  b\"\"\""), "a\n  b");
+
+    // Blank lines don't count, and a closing line with text is kept.
+    assert_eq!(string_value("\"\"\"\n  a\n\n  b\n  \"\"\""), "a\n\nb");
+    assert_eq!(string_value("\"\"\"\n\ta\n\t\tb\"\"\""), "a\n\tb");
+
+    // Escapes are decoded after stripping, and `${` is literal. An escaped
+    // quote doesn't close the string.
+    assert_eq!(
+        string_value("\"\"\"\n  \\ta ${x}\n   \\\"\"\"\n  \"\"\""),
+        "\ta ${x}\n \"\"\""
+    );
+    assert_eq!(string_value("\"\"\"\"\"\""), "");
+}
+
+#[test]
+fn malformed_multi_line_strings() {
+    let (tokens, errors) = scan("\"\"\"\n  abc\n\"\"");
+    assert_eq!(error_spans(&errors), vec![("Unterminated string", 0, 12)]);
+    assert!(tokens[0].is(&TokenType::Error));
+
+    let (tokens, errors) = scan("\"\"\"\n  a\\qb\n  \"\"\"");
+    assert_eq!(
+        error_spans(&errors),
+        vec![("Unknown escape sequence '\\q'", 7, 9)]
+    );
+    assert!(tokens[0].is(&TokenType::Error));
+    assert_eq!(lexemes(&tokens), vec!["\"\"\"\n  a\\qb\n  \"\"\"", ""]);
+}
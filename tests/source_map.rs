@@ -0,0 +1,96 @@
+use std::fs;
+
+use lox_ast::dialect::*;
+use lox_ast::scanner::*;
+use lox_ast::source_map::*;
+use lox_ast::span::*;
+
+fn source(text: &str) -> String {
+    let mut sources = SourceMap::new();
+    let file = sources.add("test.lox".to_string(), text.to_string());
+    sources.get(file).source().to_string()
+}
+
+#[test]
+fn sources_are_normalized() {
+    assert_eq!(source("\u{feff}print 1;"), "print 1;");
+    assert_eq!(source("a;\r\nb;\r\n"), "a;\nb;\n");
+    // A lone CR isn't a line ending.
+    assert_eq!(source("a;\rb;"), "a;\rb;");
+    // Only a leading BOM is dropped.
+    assert_eq!(source("a\u{feff}"), "a\u{feff}");
+}
+
+#[test]
+fn shebang_lines_are_blanked() {
+    assert_eq!(source("#!/usr/bin/env lox\nprint 1;\n"), "\nprint 1;\n");
+    assert_eq!(source("#!/usr/bin/env lox"), "");
+    assert_eq!(source("\u{feff}#!lox\r\nprint 1;"), "\nprint 1;");
+    // Only on the first line.
+    assert_eq!(source("print 1;\n#!lox"), "print 1;\n#!lox");
+}
+
+#[test]
+fn line_numbers_survive_normalization() {
+    let mut sources = SourceMap::new();
+    let file = sources.add(
+        "script.lox".to_string(),
+        "\u{feff}#!/usr/bin/env lox\r\nvar a;\r\n  @".to_string(),
+    );
+
+    let mut scanner = Scanner::new(sources.get(file).source(), Dialect::default()).file(file);
+    let (tokens, errors) = scanner.scan_tokens();
+    assert_eq!(tokens[0].span.start_line, 2);
+    assert_eq!(sources.location(&tokens[0].span), "script.lox:2:1");
+    assert_eq!(
+        sources.location(&errors[0].span().unwrap()),
+        "script.lox:3:3"
+    );
+}
+
+#[test]
+fn locations_name_the_file_line_and_column() {
+    let mut sources = SourceMap::new();
+    let first = sources.add("first.lox".to_string(), "a\nbc\n".to_string());
+    let second = sources.add("second.lox".to_string(), "héllo wörld\nx".to_string());
+
+    let at = |file: FileId, start: usize| Span {
+        file,
+        start,
+        ..Span::default()
+    };
+    assert_eq!(sources.location(&at(first, 0)), "first.lox:1:1");
+    assert_eq!(sources.location(&at(first, 3)), "first.lox:2:2");
+    // The offset just past the final newline is on a line of its own.
+    assert_eq!(sources.location(&at(first, 5)), "first.lox:3:1");
+
+    // Columns count characters: `ö` is the 8th character but the 9th byte.
+    assert_eq!(sources.location(&at(second, 8)), "second.lox:1:8");
+    assert_eq!(sources.location(&at(second, 11)), "second.lox:1:10");
+    // Offsets on the last line, including the end of the source.
+    assert_eq!(sources.location(&at(second, 14)), "second.lox:2:1");
+    assert_eq!(sources.location(&at(second, 15)), "second.lox:2:2");
+
+    assert_eq!(sources.get(second).line_col(8), (1, 8));
+}
+
+#[test]
+fn loaded_files_are_named_by_their_path() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("loaded.lox");
+    fs::write(&path, "\u{feff}print 1;\r\nprint 2;").expect("Couldn't write test file");
+
+    let mut sources = SourceMap::new();
+    let file = sources.load(&path).expect("Couldn't load test file");
+    assert_eq!(sources.get(file).source(), "print 1;\nprint 2;");
+    assert_eq!(sources.get(file).name(), path.display().to_string());
+    assert_eq!(
+        sources.location(&Span {
+            file,
+            start: 9,
+            ..Span::default()
+        }),
+        format!("{}:2:1", path.display())
+    );
+
+    assert!(sources.load(&path.with_extension("missing")).is_err());
+}